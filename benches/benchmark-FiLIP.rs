@@ -16,11 +16,13 @@ fn bench(c: &mut Criterion) {
 
         let (mut encrypter, mut decrypter) = Encrypter::<bool>::new::<EncryptedKeyBit>(
             &sys,
+            generator.random_uniform(),
             Some(&sk),
             Some(level),
             Some(base_log),
             Some(std_dev),
-        );
+        )
+        .unwrap();
 
         let message = vec![generator.random_uniform_binary::<u8>() == 1];
         let ciphertext = vec![false];
@@ -0,0 +1,140 @@
+//! Exact computations over the truth table of small functions, entry `x` of a table being the
+//! value of the function on the inputs `x_j = (x >> j) & 1`.
+
+/// Coefficients of the algebraic normal form, by Möbius transform.
+pub(super) fn anf(table: &[bool]) -> Vec<bool> {
+    let mut anf = table.to_vec();
+    let mut step = 1;
+    while step < anf.len() {
+        for x in 0..anf.len() {
+            if x & step != 0 {
+                anf[x] ^= anf[x ^ step];
+            }
+        }
+        step *= 2;
+    }
+    anf
+}
+
+pub(super) fn degree(table: &[bool]) -> usize {
+    anf(table)
+        .iter()
+        .enumerate()
+        .filter(|&(_, &c)| c)
+        .map(|(u, _)| u.count_ones() as usize)
+        .max()
+        .unwrap_or(0)
+}
+
+/// Walsh transform `W_f(a) = sum_x (-1)^(f(x) + a.x)`.
+pub(super) fn walsh(table: &[bool]) -> Vec<i64> {
+    let mut walsh = table
+        .iter()
+        .map(|&b| if b { -1 } else { 1 })
+        .collect::<Vec<i64>>();
+    let mut step = 1;
+    while step < walsh.len() {
+        for x in 0..walsh.len() {
+            if x & step == 0 {
+                let (u, v) = (walsh[x], walsh[x | step]);
+                walsh[x] = u + v;
+                walsh[x | step] = u - v;
+            }
+        }
+        step *= 2;
+    }
+    walsh
+}
+
+/// Monomials of degree at most `e` over `n` variables, the constant one first.
+fn monomials(n: usize, e: usize) -> Vec<usize> {
+    (0..1 << n)
+        .filter(|u: &usize| u.count_ones() as usize <= e)
+        .collect()
+}
+
+fn rank(mut vectors: Vec<Vec<u64>>) -> usize {
+    let words = vectors.first().map_or(0, Vec::len);
+    let mut rank = 0;
+    for bit in 0..64 * words {
+        let (w, mask) = (bit / 64, 1 << (bit % 64));
+        if let Some(p) = (rank..vectors.len()).find(|&i| vectors[i][w] & mask != 0) {
+            vectors.swap(rank, p);
+            let pivot = vectors[rank].clone();
+            for vector in vectors[rank + 1..].iter_mut() {
+                if vector[w] & mask != 0 {
+                    vector.iter_mut().zip(&pivot).for_each(|(a, b)| *a ^= b);
+                }
+            }
+            rank += 1;
+        }
+    }
+    rank
+}
+
+fn bitset(len: usize, bits: impl Iterator<Item = bool>) -> Vec<u64> {
+    let mut set = vec![0; (len + 63) / 64];
+    for (i, _) in bits.enumerate().filter(|&(_, b)| b) {
+        set[i / 64] |= 1 << (i % 64);
+    }
+    set
+}
+
+/// Whether a non-zero function of degree at most `e` vanishes on every input of `support`.
+fn has_annihilator(support: &[usize], n: usize, e: usize) -> bool {
+    let monomials = monomials(n, e);
+    let rows = support
+        .iter()
+        .map(|&x| bitset(monomials.len(), monomials.iter().map(|&u| x & u == u)))
+        .collect::<Vec<_>>();
+    rank(rows) < monomials.len()
+}
+
+/// Minimal degree of a non-zero annihilator of `f` or `f + 1`.
+pub(super) fn algebraic_immunity(table: &[bool]) -> usize {
+    let n = table.len().trailing_zeros() as usize;
+    let support = |value: bool| {
+        (0..table.len())
+            .filter(|&x| table[x] == value)
+            .collect::<Vec<_>>()
+    };
+    let (ones, zeros) = (support(true), support(false));
+    (0..=n)
+        .find(|&e| has_annihilator(&ones, n, e) || has_annihilator(&zeros, n, e))
+        .unwrap()
+}
+
+/// `min(2 AI(f), min_{1 <= deg g < AI(f)} deg g + deg fg)`, for `ai` the algebraic immunity of
+/// the function.
+pub(super) fn fast_algebraic_immunity(table: &[bool], ai: usize) -> usize {
+    let n = table.len().trailing_zeros() as usize;
+    let mut best = 2 * ai;
+    for e in 1..ai {
+        let products = monomials(n, e)
+            .iter()
+            .map(|&g| {
+                let product = (0..table.len())
+                    .map(|x| table[x] && x & g == g)
+                    .collect::<Vec<_>>();
+                anf(&product)
+            })
+            .collect::<Vec<_>>();
+        // The constant `g = 1` leads to `deg fg <= d` as soon as `deg f <= d`, and does not count.
+        for d in (0..n).take_while(|&d| e + d < best) {
+            let high = (0..table.len())
+                .filter(|u: &usize| u.count_ones() as usize > d)
+                .collect::<Vec<_>>();
+            let columns = products
+                .iter()
+                .map(|product| bitset(high.len(), high.iter().map(|&u| product[u])))
+                .collect::<Vec<_>>();
+            let constant_fits = columns[0].iter().all(|&w| w == 0);
+            let kernel = products.len() - rank(columns);
+            if kernel > usize::from(constant_fits) {
+                best = e + d;
+                break;
+            }
+        }
+    }
+    best
+}
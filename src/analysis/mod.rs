@@ -0,0 +1,405 @@
+//! Cryptographic properties of filters. Sub-filters are analysed exactly, from their truth
+//! table when they are small or from their structure otherwise, and direct sums are combined
+//! with the known formulas. When no exact formula applies, the algebraic immunities are given
+//! as bounds.
+
+mod exact;
+mod security;
+mod symmetric;
+
+use std::ops::RangeInclusive;
+
+use crate::filter::FilterExpression;
+
+pub use security::SecurityEstimate;
+
+/// Sub-filters up to this number of inputs have their algebraic immunities computed exactly.
+const EXACT_LIMIT: usize = 10;
+/// Sub-filters given by an ANF or a truth table are expanded up to this number of inputs.
+const TRUTH_TABLE_LIMIT: usize = 20;
+/// Symmetric sub-filters are analysed up to this number of inputs, beyond which their Walsh
+/// coefficients no longer fit in an `i128`.
+const SYMMETRIC_LIMIT: usize = 125;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct FilterProperties {
+    n: usize,
+    degree: usize,
+    algebraic_immunity: RangeInclusive<usize>,
+    fast_algebraic_immunity: RangeInclusive<usize>,
+    walsh_weight: usize,
+    correlation: f64,
+    linear: usize,
+    monomials: Option<Vec<usize>>,
+}
+
+impl FilterProperties {
+    /// Properties of `expression`, or `None` when one of its sub-filters is too large to be
+    /// analysed: an ANF or a truth table over more than 20 inputs, or a symmetric function over
+    /// more than 125 inputs.
+    pub fn new(expression: &FilterExpression) -> Option<Self> {
+        let properties = match expression {
+            FilterExpression::Xor(terms) => terms
+                .iter()
+                .map(Self::new)
+                .collect::<Option<Vec<_>>>()?
+                .into_iter()
+                .reduce(Self::direct_sum)?,
+            FilterExpression::Linear(k) => Self {
+                n: *k,
+                degree: 1,
+                algebraic_immunity: 1..=1,
+                fast_algebraic_immunity: 2..=2,
+                walsh_weight: *k,
+                correlation: 0.,
+                linear: *k,
+                monomials: Some(vec![*k]),
+            },
+            FilterExpression::Monomial(1) => Self::new(&FilterExpression::Linear(1))?,
+            FilterExpression::Monomial(d) => Self {
+                n: *d,
+                degree: *d,
+                algebraic_immunity: 1..=1,
+                fast_algebraic_immunity: 2..=2,
+                walsh_weight: 0,
+                correlation: (1. - 2_f64.powi(1 - *d as i32)).log2(),
+                linear: 0,
+                monomials: Some((1..=*d).map(|i| usize::from(i == *d)).collect()),
+            },
+            FilterExpression::Threshold { m, d } => {
+                Self::symmetric(&(0..=*m).map(|w| w >= *d).collect::<Vec<_>>())?
+            }
+            FilterExpression::Majority(m) => {
+                Self::symmetric(&(0..=*m).map(|w| w > m / 2).collect::<Vec<_>>())?
+            }
+            FilterExpression::Symmetric(table) => Self::symmetric(table)?,
+            FilterExpression::Anf { .. } | FilterExpression::Table(_) => {
+                let n = expression.arity();
+                if n > TRUTH_TABLE_LIMIT {
+                    return None;
+                }
+                let table = (0..1_usize << n)
+                    .map(|x| {
+                        let input = (0..n).map(|i| (x >> i) & 1 == 1).collect::<Vec<_>>();
+                        expression.call(&input)
+                    })
+                    .collect::<Vec<_>>();
+                Self::truth_table(&table)
+            }
+        };
+        Some(properties)
+    }
+
+    fn truth_table(table: &[bool]) -> Self {
+        let n = table.len().trailing_zeros() as usize;
+        let degree = exact::degree(table);
+        let walsh = exact::walsh(table);
+        let max = walsh.iter().map(|w| w.abs()).max().unwrap();
+        let walsh_weight = (0..walsh.len())
+            .filter(|&a| walsh[a] != 0)
+            .map(|a| a.count_ones() as usize)
+            .min()
+            .unwrap();
+        let anf = exact::anf(table);
+        let linear = (0..n)
+            .filter(|&i| {
+                anf[1 << i] && (0..anf.len()).all(|u| !anf[u] || u == 1 << i || u & (1 << i) == 0)
+            })
+            .count();
+        let mut properties = Self {
+            n,
+            degree,
+            algebraic_immunity: 0..=0,
+            fast_algebraic_immunity: 0..=0,
+            walsh_weight,
+            correlation: (max as f64).log2() - n as f64,
+            linear,
+            monomials: None,
+        };
+        properties.set_algebraic_immunity(table, None);
+        properties
+    }
+
+    fn symmetric(table: &[bool]) -> Option<Self> {
+        let m = table.len() - 1;
+        let walsh = symmetric::walsh(table)?;
+        let max = walsh.iter().map(|w| w.abs()).max().unwrap();
+        let degree = symmetric::degree(table);
+        let mut properties = Self {
+            n: m,
+            degree,
+            algebraic_immunity: 0..=0,
+            fast_algebraic_immunity: 0..=0,
+            walsh_weight: walsh.iter().position(|&w| w != 0).unwrap(),
+            correlation: (max as f64).log2() - m as f64,
+            linear: if degree == 1 { m } else { 0 },
+            monomials: None,
+        };
+        let expanded = (0..1_usize << m.min(EXACT_LIMIT))
+            .map(|x| table[x.count_ones() as usize])
+            .collect::<Vec<_>>();
+        properties
+            .set_algebraic_immunity(&expanded, symmetric::threshold_algebraic_immunity(table));
+        Some(properties)
+    }
+
+    /// Sets the algebraic immunities, computed on the truth table `table` for small functions,
+    /// or bounded from the degree otherwise.
+    fn set_algebraic_immunity(&mut self, table: &[bool], known: Option<usize>) {
+        if self.n <= EXACT_LIMIT {
+            let ai = exact::algebraic_immunity(table);
+            let fai = exact::fast_algebraic_immunity(table, ai);
+            self.algebraic_immunity = ai..=ai;
+            self.fast_algebraic_immunity = fai..=fai;
+        } else {
+            self.algebraic_immunity = match known {
+                Some(ai) => ai..=ai,
+                None => 1..=self.degree.min((self.n + 1) / 2),
+            };
+            self.fast_algebraic_immunity = Self::fai_bounds(&self.algebraic_immunity);
+        }
+    }
+
+    /// `AI + 1 <= FAI <= 2 AI`, the lower bound holding since `fg` annihilates `f + 1`.
+    fn fai_bounds(ai: &RangeInclusive<usize>) -> RangeInclusive<usize> {
+        if *ai.end() == 1 {
+            2..=2
+        } else {
+            ai.start() + 1..=2 * ai.end()
+        }
+    }
+
+    /// Properties of `f(x) + g(y)` over disjoint inputs. The Walsh transform of a direct sum is
+    /// the product of the transforms, which gives the resiliency and nonlinearity exactly. The
+    /// algebraic immunity of a direct sum of monomials is `min_d (d + sum_{i > d} m_i)`, for
+    /// `m_i` monomials of degree `i`, and is otherwise bounded by
+    /// `max(AI(f), AI(g)) <= AI(f + g) <= AI(f) + AI(g)`.
+    fn direct_sum(self, other: Self) -> Self {
+        let n = self.n + other.n;
+        let degree = self.degree.max(other.degree);
+        let monomials = match (self.monomials, other.monomials) {
+            (Some(a), Some(b)) => Some(
+                (0..a.len().max(b.len()))
+                    .map(|i| a.get(i).unwrap_or(&0) + b.get(i).unwrap_or(&0))
+                    .collect::<Vec<_>>(),
+            ),
+            _ => None,
+        };
+        let algebraic_immunity = match &monomials {
+            Some(m) => {
+                let ai = (0..=m.len())
+                    .map(|d| d + m[d..].iter().sum::<usize>())
+                    .min()
+                    .unwrap();
+                ai..=ai
+            }
+            None => {
+                let (a, b) = (&self.algebraic_immunity, &other.algebraic_immunity);
+                let upper = (a.end() + b.end()).min(degree).min((n + 1) / 2);
+                *a.start().max(b.start())..=upper
+            }
+        };
+        Self {
+            n,
+            degree,
+            fast_algebraic_immunity: Self::fai_bounds(&algebraic_immunity),
+            algebraic_immunity,
+            walsh_weight: self.walsh_weight + other.walsh_weight,
+            correlation: self.correlation + other.correlation,
+            linear: self.linear + other.linear,
+            monomials,
+        }
+    }
+
+    /// Number of inputs.
+    pub fn n(&self) -> usize {
+        self.n
+    }
+
+    pub fn degree(&self) -> usize {
+        self.degree
+    }
+
+    /// Algebraic immunity, as a single value when it is known exactly.
+    pub fn algebraic_immunity(&self) -> RangeInclusive<usize> {
+        self.algebraic_immunity.clone()
+    }
+
+    /// Fast algebraic immunity, as a single value when it is known exactly.
+    pub fn fast_algebraic_immunity(&self) -> RangeInclusive<usize> {
+        self.fast_algebraic_immunity.clone()
+    }
+
+    pub fn is_balanced(&self) -> bool {
+        self.walsh_weight > 0
+    }
+
+    /// Largest `r` such that the filter is balanced on every restriction fixing `r` inputs, or
+    /// `None` when the filter is not balanced.
+    pub fn resiliency(&self) -> Option<usize> {
+        self.walsh_weight.checked_sub(1)
+    }
+
+    /// `log2(max_a |W_f(a)| / 2^n)`, the largest correlation between the filter and an affine
+    /// function, which drives correlation attacks.
+    pub fn correlation(&self) -> f64 {
+        self.correlation
+    }
+
+    /// Number of inputs that only appear in the linear part of the filter.
+    pub fn linear_inputs(&self) -> usize {
+        self.linear
+    }
+
+    /// `log2` of the distance to the affine functions, `2^(n - 1) (1 - 2^correlation)`, which
+    /// would overflow an `f64` beyond a thousand inputs. It is minus infinity for affine filters.
+    pub fn nonlinearity(&self) -> f64 {
+        (self.n as f64 - 1.) + (-self.correlation.exp2()).ln_1p() / std::f64::consts::LN_2
+    }
+
+    /// Properties that make the filter unfit for a stream cipher, whatever the key size.
+    pub fn flaws(&self) -> Vec<String> {
+        let mut flaws = Vec::new();
+        if !self.is_balanced() {
+            flaws.push("the filter is not balanced".to_string());
+        }
+        if self.degree < 2 {
+            flaws.push("the filter is affine".to_string());
+        }
+        if *self.algebraic_immunity.end() < 2 {
+            flaws.push("the filter has a degree one annihilator".to_string());
+        }
+        flaws
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::filter::{Filter, FilterType};
+
+    fn exact(expression: &FilterExpression) -> FilterProperties {
+        let n = expression.arity();
+        let table = (0..1_usize << n)
+            .map(|x| {
+                let input = (0..n).map(|i| (x >> i) & 1 == 1).collect::<Vec<_>>();
+                expression.call(&input)
+            })
+            .collect::<Vec<_>>();
+        FilterProperties::truth_table(&table)
+    }
+
+    fn assert_consistent(expression: &FilterExpression) {
+        let combined = FilterProperties::new(expression).unwrap();
+        let exact = exact(expression);
+        assert_eq!(combined.degree(), exact.degree(), "{:?}", expression);
+        assert_eq!(
+            combined.resiliency(),
+            exact.resiliency(),
+            "{:?}",
+            expression
+        );
+        assert!((combined.correlation() - exact.correlation()).abs() < 1e-9);
+        let ai = *exact.algebraic_immunity().start();
+        let fai = *exact.fast_algebraic_immunity().start();
+        assert!(
+            combined.algebraic_immunity().contains(&ai),
+            "{:?}",
+            expression
+        );
+        assert!(
+            combined.fast_algebraic_immunity().contains(&fai),
+            "{:?}",
+            expression
+        );
+    }
+
+    #[test]
+    fn direct_sums_match_truth_tables() {
+        use FilterExpression::*;
+        for expression in [
+            Xor(vec![Linear(2), Monomial(2), Monomial(3)]),
+            Xor(vec![Monomial(2), Monomial(2), Monomial(3), Monomial(3)]),
+            Xor(vec![Linear(3), Threshold { m: 6, d: 3 }]),
+            Xor(vec![Linear(2), Majority(7)]),
+            Xor(vec![
+                Monomial(2),
+                Symmetric(vec![false, true, true, false, true]),
+            ]),
+            Xor(vec![Linear(1), FilterExpression::wpb(8)]),
+            Xor(vec![Threshold { m: 4, d: 2 }, Majority(5)]),
+        ] {
+            assert_consistent(&expression);
+        }
+    }
+
+    #[test]
+    fn symmetric_functions_match_truth_tables() {
+        for m in 1..8 {
+            for values in 1..(1_usize << (m + 1)) - 1 {
+                let table = (0..=m).map(|w| (values >> w) & 1 == 1).collect::<Vec<_>>();
+                let expression = FilterExpression::Symmetric(table);
+                assert_eq!(FilterProperties::new(&expression), Some(exact(&expression)));
+            }
+        }
+    }
+
+    #[test]
+    fn thresholds() {
+        // Large enough to go through the closed formula rather than the truth table.
+        for (m, d) in [(11, 3), (12, 6), (13, 7), (15, 15)] {
+            let properties = FilterProperties::new(&FilterExpression::Threshold { m, d }).unwrap();
+            let ai = d.min(m - d + 1);
+            assert_eq!(properties.algebraic_immunity(), ai..=ai);
+        }
+    }
+
+    #[test]
+    fn parameter_sets() {
+        let dsm = Filter::new(FilterType::DSM, &[128, 64, 0, 80, 0, 0, 0, 80]);
+        let properties = FilterProperties::new(&dsm.expression(1216)).unwrap();
+        assert_eq!(properties.n(), 1216);
+        assert_eq!(properties.degree(), 8);
+        assert_eq!(properties.algebraic_immunity(), 8..=8);
+        assert_eq!(properties.resiliency(), Some(127));
+        // Just below 2^1215, far beyond the range of an f64.
+        assert!(properties.nonlinearity() > 1214. && properties.nonlinearity() <= 1215.);
+
+        let xor_thr = Filter::new(FilterType::XorThr, &[81, 32]);
+        let properties = FilterProperties::new(&xor_thr.expression(144)).unwrap();
+        assert_eq!(properties.degree(), 32);
+        assert_eq!(properties.algebraic_immunity(), 32..=32);
+        assert_eq!(properties.fast_algebraic_immunity(), 33..=64);
+        assert_eq!(properties.resiliency(), Some(81));
+        assert!(properties.flaws().is_empty());
+
+        let unbalanced = Filter::new(FilterType::DSM, &[0, 3]);
+        assert_eq!(
+            FilterProperties::new(&unbalanced.expression(6))
+                .unwrap()
+                .flaws()
+                .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn nonlinearity() {
+        let properties = FilterProperties::new(&FilterExpression::Monomial(3)).unwrap();
+        assert!((properties.nonlinearity() - 0.).abs() < 1e-9);
+        let properties = FilterProperties::new(&FilterExpression::Linear(3)).unwrap();
+        assert_eq!(properties.nonlinearity(), f64::NEG_INFINITY);
+    }
+
+    #[test]
+    fn too_large() {
+        use FilterExpression::*;
+        let anf = Anf {
+            arity: 21,
+            monomials: (0..21).map(|i| vec![i]).collect(),
+        };
+        assert_eq!(FilterProperties::new(&Xor(vec![Linear(2), anf])), None);
+        assert_eq!(FilterProperties::new(&Majority(127)), None);
+        assert!(FilterProperties::new(&Majority(125)).is_some());
+    }
+}
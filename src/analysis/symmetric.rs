@@ -0,0 +1,70 @@
+//! Properties of symmetric functions from their value on each Hamming weight, without expanding
+//! the truth table.
+
+use super::SYMMETRIC_LIMIT;
+
+/// Binomial coefficients `C(m, k)` for `m` up to `len - 1`.
+fn binomials(len: usize) -> Vec<Vec<i128>> {
+    let mut binomials = vec![vec![0_i128; len + 1]; len];
+    for m in 0..len {
+        binomials[m][0] = 1;
+        for k in 1..=m {
+            binomials[m][k] = binomials[m - 1][k - 1] + binomials[m - 1][k];
+        }
+    }
+    binomials
+}
+
+/// The monomials of weight `u` appear in the algebraic normal form when the xor of the values
+/// on the weights `w` whose binary decomposition is covered by `u` is one.
+pub(super) fn degree(table: &[bool]) -> usize {
+    (0..table.len())
+        .rev()
+        .find(|&u| {
+            (0..=u)
+                .filter(|&w| w & u == w)
+                .fold(false, |acc, w| acc ^ table[w])
+        })
+        .unwrap_or(0)
+}
+
+/// Walsh transform on the inputs of each Hamming weight `j`, as the Krawtchouk expansion
+/// `sum_w (-1)^f(w) K_w(j)` with `K_w(j) = sum_i (-1)^i C(j, i) C(m - j, w - i)`, or `None`
+/// beyond `SYMMETRIC_LIMIT` inputs, where it no longer fits in an `i128`.
+pub(super) fn walsh(table: &[bool]) -> Option<Vec<i128>> {
+    let m = table.len() - 1;
+    if m > SYMMETRIC_LIMIT {
+        return None;
+    }
+    let c = binomials(m + 1);
+    let walsh = (0..=m)
+        .map(|j| {
+            (0..=m)
+                .map(|w| {
+                    let k = (w.saturating_sub(m - j)..=w.min(j))
+                        .map(|i| c[j][i] * c[m - j][w - i] * if i % 2 == 0 { 1 } else { -1 })
+                        .sum::<i128>();
+                    if table[w] {
+                        -k
+                    } else {
+                        k
+                    }
+                })
+                .sum()
+        })
+        .collect();
+    Some(walsh)
+}
+
+/// Algebraic immunity `min(d, m - d + 1)` of the threshold function `T_{d, m}` and of its
+/// complement, when the table is one of them.
+pub(super) fn threshold_algebraic_immunity(table: &[bool]) -> Option<usize> {
+    let m = table.len() - 1;
+    let changes = (1..=m)
+        .filter(|&w| table[w] != table[w - 1])
+        .collect::<Vec<_>>();
+    match changes[..] {
+        [d] => Some(d.min(m - d + 1)),
+        _ => None,
+    }
+}
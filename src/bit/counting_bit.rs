@@ -0,0 +1,94 @@
+use std::{
+    cell::RefCell,
+    ops::{BitAnd, BitAndAssign, BitXor, BitXorAssign, Not},
+    rc::Rc,
+};
+
+use concrete_commons::parameters::{GlweSize, PolynomialSize};
+
+use crate::multiplexer::{CountingKeyBit, OperationCount};
+use crate::Bit;
+
+/// Bit computed by `CountingKeyBit`: it only holds its multiplicative depth, and the count of
+/// the operations shared with the key.
+#[derive(Clone, Debug, Default)]
+pub struct CountingBit {
+    depth: usize,
+    count: Rc<RefCell<OperationCount>>,
+}
+
+impl CountingBit {
+    pub(crate) fn new(depth: usize, count: Rc<RefCell<OperationCount>>) -> Self {
+        Self { depth, count }
+    }
+
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Operations counted so far, along with the depth of this bit.
+    pub fn count(&self) -> OperationCount {
+        OperationCount {
+            depth: self.depth,
+            ..*self.count.borrow()
+        }
+    }
+
+    /// Xor, computed as the addition of the ciphertexts.
+    fn add(&mut self, rhs: &Self) {
+        self.count.borrow_mut().additions += 1;
+        self.depth = self.depth.max(rhs.depth);
+    }
+}
+
+impl Not for CountingBit {
+    type Output = Self;
+
+    fn not(mut self) -> Self {
+        self.not_inplace();
+        self
+    }
+}
+
+impl BitAndAssign<CountingKeyBit> for CountingBit {
+    fn bitand_assign(&mut self, rhs: CountingKeyBit) {
+        *self = rhs & self.clone();
+    }
+}
+
+impl BitXorAssign for CountingBit {
+    fn bitxor_assign(&mut self, rhs: Self) {
+        self.add(&rhs);
+    }
+}
+
+impl BitXor for CountingBit {
+    type Output = Self;
+
+    fn bitxor(mut self, rhs: Self) -> Self::Output {
+        self ^= rhs;
+        self
+    }
+}
+
+impl BitAnd<CountingKeyBit> for CountingBit {
+    type Output = Self;
+
+    fn bitand(self, rhs: CountingKeyBit) -> Self::Output {
+        rhs & self
+    }
+}
+
+impl Bit<CountingKeyBit> for CountingBit {
+    fn zero(_poly_size: Option<PolynomialSize>, _size: Option<GlweSize>) -> Self {
+        Self::default()
+    }
+
+    fn one(_poly_size: Option<PolynomialSize>, _size: Option<GlweSize>) -> Self {
+        Self::default()
+    }
+
+    fn not_inplace(&mut self) {
+        self.count.borrow_mut().negations += 1;
+    }
+}
@@ -1,11 +1,19 @@
 mod bit_bool;
+mod counting_bit;
 mod encrypted_bit;
+mod shadow_bit;
+mod simulated_bit;
+mod symbolic_bit;
 
 use concrete_commons::parameters::{GlweSize, PolynomialSize};
 use std::ops::{BitAndAssign, BitXor, BitXorAssign, Not};
 
 pub use bit_bool::*;
+pub use counting_bit::*;
 pub use encrypted_bit::*;
+pub use shadow_bit::*;
+pub use simulated_bit::*;
+pub use symbolic_bit::*;
 
 pub trait Bit<Other>:
     Clone + Not<Output = Self> + BitXor<Output = Self> + BitXorAssign + BitAndAssign<Other>
@@ -0,0 +1,123 @@
+use std::{
+    ops::{BitAnd, BitAndAssign, BitXor, BitXorAssign, Not},
+    rc::Rc,
+};
+
+use concrete_commons::parameters::{GlweSize, PolynomialSize};
+
+use crate::multiplexer::{either, Divergence, Operation, Shadow, ShadowKeyBit};
+use crate::{Bit, EncryptedBit};
+
+/// Bit computed by `ShadowKeyBit`: the ciphertext, and the value it should encrypt.
+#[derive(Clone)]
+pub struct ShadowBit {
+    bit: EncryptedBit,
+    value: bool,
+    shadow: Option<Rc<Shadow>>,
+}
+
+impl ShadowBit {
+    /// Result `bit` of `operation`, checked against its clear value `value`.
+    pub(crate) fn checked(
+        operation: Operation,
+        bit: EncryptedBit,
+        value: bool,
+        shadow: Option<Rc<Shadow>>,
+    ) -> Self {
+        let returnValue = Self { bit, value, shadow };
+        returnValue.check(operation);
+        returnValue
+    }
+
+    fn check(&self, operation: Operation) {
+        if let Some(shadow) = &self.shadow {
+            shadow.check(operation, &self.bit, self.value);
+        }
+    }
+
+    pub fn bit(&self) -> &EncryptedBit {
+        &self.bit
+    }
+
+    pub fn into_bit(self) -> EncryptedBit {
+        self.bit
+    }
+
+    pub fn value(&self) -> bool {
+        self.value
+    }
+
+    /// First operation that went wrong so far, over the whole key this bit was computed from.
+    pub fn divergence(&self) -> Option<Divergence> {
+        self.shadow.as_ref().and_then(|shadow| shadow.divergence())
+    }
+
+    pub(crate) fn shadow(&self) -> &Option<Rc<Shadow>> {
+        &self.shadow
+    }
+}
+
+impl Not for ShadowBit {
+    type Output = Self;
+
+    fn not(mut self) -> Self {
+        self.not_inplace();
+        self
+    }
+}
+
+impl BitAndAssign<ShadowKeyBit> for ShadowBit {
+    fn bitand_assign(&mut self, rhs: ShadowKeyBit) {
+        *self = rhs & self.clone();
+    }
+}
+
+impl BitXorAssign for ShadowBit {
+    fn bitxor_assign(&mut self, rhs: Self) {
+        self.shadow = either(&self.shadow, &rhs.shadow);
+        self.bit ^= rhs.bit;
+        self.value ^= rhs.value;
+        self.check(Operation::Addition);
+    }
+}
+
+impl BitXor for ShadowBit {
+    type Output = Self;
+
+    fn bitxor(mut self, rhs: Self) -> Self::Output {
+        self ^= rhs;
+        self
+    }
+}
+
+impl BitAnd<ShadowKeyBit> for ShadowBit {
+    type Output = Self;
+
+    fn bitand(self, rhs: ShadowKeyBit) -> Self::Output {
+        rhs & self
+    }
+}
+
+impl Bit<ShadowKeyBit> for ShadowBit {
+    fn zero(poly_size: Option<PolynomialSize>, size: Option<GlweSize>) -> Self {
+        Self {
+            bit: EncryptedBit::zero(poly_size, size),
+            value: false,
+            shadow: None,
+        }
+    }
+
+    fn one(poly_size: Option<PolynomialSize>, size: Option<GlweSize>) -> Self {
+        Self {
+            bit: EncryptedBit::one(poly_size, size),
+            value: true,
+            shadow: None,
+        }
+    }
+
+    fn not_inplace(&mut self) {
+        self.bit.not_inplace();
+        self.value = !self.value;
+        self.check(Operation::Negation);
+    }
+}
@@ -0,0 +1,86 @@
+use std::ops::{BitAnd, BitAndAssign, BitXor, BitXorAssign, Not};
+
+use concrete_commons::parameters::{GlweSize, PolynomialSize};
+
+use crate::Bit;
+use crate::{multiplexer::SimulatedKeyBit, NoiseEstimate};
+
+/// Stand-in for an `EncryptedBit`: the clear bit, along with the variance of the noise its
+/// ciphertext would hold.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SimulatedBit {
+    value: bool,
+    variance: f64,
+}
+
+impl SimulatedBit {
+    pub fn new(value: bool, variance: f64) -> Self {
+        Self { value, variance }
+    }
+
+    pub fn value(&self) -> bool {
+        self.value
+    }
+
+    pub fn variance(&self) -> f64 {
+        self.variance
+    }
+
+    /// Noise of the bit, and its probability of decoding wrong.
+    pub fn estimate(&self) -> NoiseEstimate {
+        NoiseEstimate::new(self.variance)
+    }
+}
+
+impl Not for SimulatedBit {
+    type Output = Self;
+
+    fn not(mut self) -> Self {
+        self.not_inplace();
+        self
+    }
+}
+
+impl BitAndAssign<SimulatedKeyBit> for SimulatedBit {
+    fn bitand_assign(&mut self, rhs: SimulatedKeyBit) {
+        *self = rhs & self.clone();
+    }
+}
+
+impl BitXorAssign for SimulatedBit {
+    fn bitxor_assign(&mut self, rhs: Self) {
+        self.value ^= rhs.value;
+        self.variance += rhs.variance;
+    }
+}
+
+impl BitXor for SimulatedBit {
+    type Output = Self;
+
+    fn bitxor(mut self, rhs: Self) -> Self::Output {
+        self ^= rhs;
+        self
+    }
+}
+
+impl BitAnd<SimulatedKeyBit> for SimulatedBit {
+    type Output = Self;
+
+    fn bitand(self, rhs: SimulatedKeyBit) -> Self::Output {
+        rhs & self
+    }
+}
+
+impl Bit<SimulatedKeyBit> for SimulatedBit {
+    fn zero(_poly_size: Option<PolynomialSize>, _size: Option<GlweSize>) -> Self {
+        Self::new(false, 0.)
+    }
+
+    fn one(_poly_size: Option<PolynomialSize>, _size: Option<GlweSize>) -> Self {
+        Self::new(true, 0.)
+    }
+
+    fn not_inplace(&mut self) {
+        self.value = !self.value;
+    }
+}
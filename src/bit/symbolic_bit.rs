@@ -0,0 +1,116 @@
+use std::{
+    cell::RefCell,
+    ops::{BitAnd, BitAndAssign, BitXor, BitXorAssign, Not},
+    rc::Rc,
+};
+
+use concrete_commons::parameters::{GlweSize, PolynomialSize};
+
+use crate::circuit::{Circuit, Gate};
+use crate::multiplexer::SymbolicKeyBit;
+use crate::Bit;
+
+/// Bit computed by `SymbolicKeyBit`: the wire of the shared circuit that carries it.
+#[derive(Clone, Debug)]
+pub struct SymbolicBit {
+    wire: usize,
+    circuit: Rc<RefCell<Circuit>>,
+}
+
+impl SymbolicBit {
+    pub(crate) fn new(wire: usize, circuit: Rc<RefCell<Circuit>>) -> Self {
+        Self { wire, circuit }
+    }
+
+    /// Bit of constant value, in a circuit of its own: it cannot be combined with the bits of
+    /// another circuit, whose constants come from `SymbolicKeyBit::constant` instead.
+    fn constant(value: bool) -> Self {
+        let mut circuit = Circuit::new(0);
+        let wire = circuit.push(Gate::Const(value));
+        Self::new(wire, Rc::new(RefCell::new(circuit)))
+    }
+
+    pub fn wire(&self) -> usize {
+        self.wire
+    }
+
+    /// Panics unless the bit is a wire of `circuit`: wires of different circuits cannot be
+    /// mixed, and would silently read unrelated gates.
+    pub(crate) fn assert_in(&self, circuit: &Rc<RefCell<Circuit>>) {
+        assert!(
+            Rc::ptr_eq(&self.circuit, circuit),
+            "symbolic bits of different circuits cannot be combined"
+        );
+    }
+
+    /// The whole circuit recorded so far, in which `wire()` carries this bit.
+    pub fn circuit(&self) -> Circuit {
+        self.circuit.borrow().clone()
+    }
+
+    /// Bristol Fashion netlist computing this bit, see `Circuit::to_bristol`.
+    pub fn to_bristol(&self) -> String {
+        self.circuit.borrow().to_bristol(self.wire)
+    }
+
+    /// BLIF netlist computing this bit, see `Circuit::to_blif`.
+    pub fn to_blif(&self, name: &str) -> String {
+        self.circuit.borrow().to_blif(self.wire, name)
+    }
+}
+
+impl Not for SymbolicBit {
+    type Output = Self;
+
+    fn not(mut self) -> Self {
+        self.not_inplace();
+        self
+    }
+}
+
+impl BitAndAssign<SymbolicKeyBit> for SymbolicBit {
+    fn bitand_assign(&mut self, rhs: SymbolicKeyBit) {
+        *self = rhs & self.clone();
+    }
+}
+
+impl BitXorAssign for SymbolicBit {
+    fn bitxor_assign(&mut self, rhs: Self) {
+        rhs.assert_in(&self.circuit);
+        self.wire = self
+            .circuit
+            .borrow_mut()
+            .push(Gate::Xor(self.wire, rhs.wire));
+    }
+}
+
+impl BitXor for SymbolicBit {
+    type Output = Self;
+
+    fn bitxor(mut self, rhs: Self) -> Self::Output {
+        self ^= rhs;
+        self
+    }
+}
+
+impl BitAnd<SymbolicKeyBit> for SymbolicBit {
+    type Output = Self;
+
+    fn bitand(self, rhs: SymbolicKeyBit) -> Self::Output {
+        rhs & self
+    }
+}
+
+impl Bit<SymbolicKeyBit> for SymbolicBit {
+    fn zero(_poly_size: Option<PolynomialSize>, _size: Option<GlweSize>) -> Self {
+        Self::constant(false)
+    }
+
+    fn one(_poly_size: Option<PolynomialSize>, _size: Option<GlweSize>) -> Self {
+        Self::constant(true)
+    }
+
+    fn not_inplace(&mut self) {
+        self.wire = self.circuit.borrow_mut().push(Gate::Inv(self.wire));
+    }
+}
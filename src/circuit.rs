@@ -0,0 +1,331 @@
+//! Boolean circuits of the filters, as recorded by `SymbolicKeyBit`, and their export to the
+//! Bristol Fashion and BLIF netlist formats.
+
+use std::fmt::Write;
+
+/// Gate of a `Circuit`, reading the wires it is given.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Gate {
+    Xor(usize, usize),
+    And(usize, usize),
+    Inv(usize),
+    /// `one` if `select` is set, `zero` otherwise.
+    Mux {
+        select: usize,
+        one: usize,
+        zero: usize,
+    },
+    Const(bool),
+}
+
+impl Gate {
+    fn wires(&self) -> Vec<usize> {
+        match *self {
+            Self::Xor(a, b) | Self::And(a, b) => vec![a, b],
+            Self::Inv(a) => vec![a],
+            Self::Mux { select, one, zero } => vec![select, one, zero],
+            Self::Const(_) => vec![],
+        }
+    }
+}
+
+/// Boolean circuit over `inputs` input wires, numbered from zero, each gate driving the next
+/// wire.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Circuit {
+    inputs: usize,
+    gates: Vec<Gate>,
+}
+
+impl Circuit {
+    pub fn new(inputs: usize) -> Self {
+        Self {
+            inputs,
+            gates: Vec::new(),
+        }
+    }
+
+    pub fn inputs(&self) -> usize {
+        self.inputs
+    }
+
+    pub fn gates(&self) -> &[Gate] {
+        &self.gates
+    }
+
+    /// Adds `gate`, and returns the wire it drives.
+    pub fn push(&mut self, gate: Gate) -> usize {
+        self.gates.push(gate);
+        self.inputs + self.gates.len() - 1
+    }
+
+    /// Whether each gate contributes to `output`: the filters compute some values they end up
+    /// not using, such as the weights the threshold accumulator drops.
+    fn live(&self, output: usize) -> Vec<bool> {
+        let mut live = vec![false; self.gates.len()];
+        if output >= self.inputs {
+            live[output - self.inputs] = true;
+        }
+        for i in (0..self.gates.len()).rev() {
+            if live[i] {
+                for wire in self.gates[i].wires() {
+                    if wire >= self.inputs {
+                        live[wire - self.inputs] = true;
+                    }
+                }
+            }
+        }
+        live
+    }
+
+    /// Gates `output` depends on, in order.
+    pub fn live_gates(&self, output: usize) -> Vec<Gate> {
+        self.gates
+            .iter()
+            .zip(self.live(output))
+            .filter(|&(_, live)| live)
+            .map(|(&gate, _)| gate)
+            .collect()
+    }
+
+    /// Value of `output` on `inputs`.
+    pub fn evaluate(&self, inputs: &[bool], output: usize) -> bool {
+        assert_eq!(inputs.len(), self.inputs, "one value per input wire");
+        let mut values = inputs.to_vec();
+        for gate in &self.gates {
+            let value = match *gate {
+                Gate::Xor(a, b) => values[a] ^ values[b],
+                Gate::And(a, b) => values[a] & values[b],
+                Gate::Inv(a) => !values[a],
+                Gate::Mux { select, one, zero } => {
+                    if values[select] {
+                        values[one]
+                    } else {
+                        values[zero]
+                    }
+                }
+                Gate::Const(value) => value,
+            };
+            values.push(value);
+        }
+        values[output]
+    }
+
+    /// Bristol Fashion netlist of `output`: one input value made of the input wires, and one
+    /// output value of one bit. The format has no multiplexer, which becomes
+    /// `zero ^ (select & (one ^ zero))`, and the output is copied to the last wire when it
+    /// does not already end up there.
+    pub fn to_bristol(&self, output: usize) -> String {
+        let mut renumbered = (0..self.inputs).map(Some).collect::<Vec<_>>();
+        renumbered.resize(self.inputs + self.gates.len(), None);
+        let mut next = self.inputs;
+        let mut lines = Vec::new();
+        let mut emit = |line: String, next: &mut usize| {
+            lines.push(line);
+            *next += 1;
+            *next - 1
+        };
+        for (i, (gate, live)) in self.gates.iter().zip(self.live(output)).enumerate() {
+            if !live {
+                continue;
+            }
+            let w = |wire: usize| renumbered[wire].unwrap();
+            let wire = match *gate {
+                Gate::Xor(a, b) => emit(format!("2 1 {} {} {} XOR", w(a), w(b), next), &mut next),
+                Gate::And(a, b) => emit(format!("2 1 {} {} {} AND", w(a), w(b), next), &mut next),
+                Gate::Inv(a) => emit(format!("1 1 {} {} INV", w(a), next), &mut next),
+                Gate::Mux { select, one, zero } => {
+                    let diff = emit(
+                        format!("2 1 {} {} {} XOR", w(one), w(zero), next),
+                        &mut next,
+                    );
+                    let masked = emit(
+                        format!("2 1 {} {} {} AND", w(select), diff, next),
+                        &mut next,
+                    );
+                    emit(
+                        format!("2 1 {} {} {} XOR", w(zero), masked, next),
+                        &mut next,
+                    )
+                }
+                Gate::Const(value) => emit(format!("1 1 {} {} EQ", value as u8, next), &mut next),
+            };
+            renumbered[self.inputs + i] = Some(wire);
+        }
+        let out = renumbered[output].unwrap();
+        if out + 1 != next || out < self.inputs {
+            emit(format!("1 1 {} {} EQW", out, next), &mut next);
+        }
+
+        let mut returnValue = format!("{} {}\n1 {}\n1 1\n\n", lines.len(), next, self.inputs);
+        for line in lines {
+            writeln!(returnValue, "{}", line).unwrap();
+        }
+        returnValue
+    }
+
+    /// BLIF netlist of `output`, as the model `name`, with inputs `x0`, `x1`, ... and output
+    /// `y`.
+    pub fn to_blif(&self, output: usize, name: &str) -> String {
+        let wire = |wire: usize| {
+            if wire < self.inputs {
+                format!("x{}", wire)
+            } else {
+                format!("w{}", wire - self.inputs)
+            }
+        };
+        let mut returnValue = format!(".model {}\n.inputs", name);
+        for i in 0..self.inputs {
+            write!(returnValue, " x{}", i).unwrap();
+        }
+        returnValue.push_str("\n.outputs y\n");
+        for (i, (gate, live)) in self.gates.iter().zip(self.live(output)).enumerate() {
+            if !live {
+                continue;
+            }
+            let out = wire(self.inputs + i);
+            let (wires, cover) = match *gate {
+                Gate::Xor(a, b) => (vec![a, b], "01 1\n10 1\n"),
+                Gate::And(a, b) => (vec![a, b], "11 1\n"),
+                Gate::Inv(a) => (vec![a], "0 1\n"),
+                Gate::Mux { select, one, zero } => (vec![select, one, zero], "11- 1\n0-1 1\n"),
+                Gate::Const(true) => (vec![], "1\n"),
+                Gate::Const(false) => (vec![], ""),
+            };
+            returnValue.push_str(".names");
+            for w in wires {
+                write!(returnValue, " {}", wire(w)).unwrap();
+            }
+            writeln!(returnValue, " {}", out).unwrap();
+            returnValue.push_str(cover);
+        }
+        writeln!(returnValue, ".names {} y\n1 1\n.end", wire(output)).unwrap();
+        returnValue
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        filter::{Filter, FilterType},
+        Bit, Multiplexer, SymbolicBit, SymbolicKeyBit, ToyParameters,
+    };
+    use concrete_core::math::random::RandomGenerator;
+
+    /// Evaluates a Bristol Fashion netlist with a single input and output value.
+    fn run_bristol(netlist: &str, inputs: &[bool]) -> bool {
+        let mut lines = netlist.lines();
+        let header = lines.next().unwrap().split(' ').collect::<Vec<_>>();
+        let wires = header[1].parse().unwrap();
+        assert_eq!(lines.next(), Some(format!("1 {}", inputs.len()).as_str()));
+        assert_eq!(lines.next(), Some("1 1"));
+        assert_eq!(lines.next(), Some(""));
+        let mut values = vec![None; wires];
+        for (value, &input) in values.iter_mut().zip(inputs) {
+            *value = Some(input);
+        }
+        for line in lines {
+            let fields = line.split(' ').collect::<Vec<_>>();
+            let wire = |i: usize| values[fields[i].parse::<usize>().unwrap()].unwrap();
+            let value = match *fields.last().unwrap() {
+                "XOR" => wire(2) ^ wire(3),
+                "AND" => wire(2) & wire(3),
+                "INV" => !wire(2),
+                "EQW" => wire(2),
+                "EQ" => fields[2] == "1",
+                gate => panic!("unknown gate {}", gate),
+            };
+            let out = fields[fields.len() - 2].parse::<usize>().unwrap();
+            assert!(values[out].is_none(), "wire {} driven twice", out);
+            values[out] = Some(value);
+        }
+        values[wires - 1].unwrap()
+    }
+
+    /// Evaluates a BLIF netlist given as a topologically sorted list of covers.
+    fn run_blif(netlist: &str, inputs: &[bool]) -> bool {
+        let mut values = std::collections::HashMap::new();
+        for (i, &input) in inputs.iter().enumerate() {
+            values.insert(format!("x{}", i), input);
+        }
+        let mut lines = netlist.lines().peekable();
+        while let Some(line) = lines.next() {
+            if let Some(names) = line.strip_prefix(".names") {
+                let names = names.split_whitespace().collect::<Vec<_>>();
+                let (out, ins) = names.split_last().unwrap();
+                let ins = ins.iter().map(|name| values[*name]).collect::<Vec<_>>();
+                let mut value = false;
+                while let Some(&row) = lines.peek().filter(|row| !row.starts_with('.')) {
+                    let cube = row.split(' ').next().unwrap();
+                    let cube = if ins.is_empty() { "" } else { cube };
+                    value |= cube
+                        .chars()
+                        .zip(&ins)
+                        .all(|(c, &x)| c == '-' || (c == '1') == x);
+                    lines.next();
+                }
+                values.insert(out.to_string(), value);
+            }
+        }
+        values["y"]
+    }
+
+    fn check(filter: &Filter, n: usize) {
+        let key = SymbolicKeyBit::key(n);
+        let bit = filter.call(&key);
+        let circuit = bit.circuit();
+        for x in 0..1_usize << n {
+            let input = (0..n).map(|i| (x >> i) & 1 == 1).collect::<Vec<_>>();
+            let expected = filter.call(&input);
+            assert_eq!(circuit.evaluate(&input, bit.wire()), expected);
+            assert_eq!(run_bristol(&bit.to_bristol(), &input), expected);
+            assert_eq!(run_blif(&bit.to_blif("filter"), &input), expected);
+        }
+    }
+
+    #[test]
+    fn filters() {
+        check(&Filter::new(FilterType::DSM, &[2, 0, 1]), 5);
+        check(&Filter::new(FilterType::XorThr, &[2, 4]), 9);
+        check(&Filter::new(FilterType::XorMaj, &[1, 5]), 6);
+        check(&Filter::parse("symmetric 0110\ntable 0001", 5).unwrap(), 5);
+
+        let bit = Filter::new(FilterType::DSM, &[2, 0, 1]).call(&SymbolicKeyBit::key(5));
+        let gates = bit.circuit().live_gates(bit.wire());
+        assert_eq!(gates.len(), 4);
+        assert_eq!(
+            gates.iter().filter(|g| matches!(g, Gate::And(..))).count(),
+            2
+        );
+    }
+
+    #[test]
+    fn keystream() {
+        let set = ToyParameters::dsm.parameter_set(true).unwrap();
+        let mut generator = RandomGenerator::new(None);
+        let nonce = generator.random_uniform();
+        let bit = set.circuit(nonce);
+        let circuit = bit.circuit();
+        assert_eq!(circuit.inputs(), set.key_size);
+        for _ in 0..16 {
+            let key = (0..set.key_size)
+                .map(|_| generator.random_uniform_binary::<u8>() == 1)
+                .collect::<Vec<_>>();
+            let mut encrypter =
+                crate::Encrypter::from_parameter_set(key.clone(), &set, nonce, true).unwrap();
+            let mut keystream = [false];
+            encrypter.encrypt(&mut keystream, &[false]);
+            assert_eq!(circuit.evaluate(&key, bit.wire()), keystream[0]);
+            assert_eq!(run_bristol(&bit.to_bristol(), &key), keystream[0]);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "different circuits")]
+    fn mixed_circuits() {
+        let key = SymbolicKeyBit::key(2);
+        let mut bit = key[0].as_bit();
+        bit ^= SymbolicBit::one(None, None);
+    }
+}
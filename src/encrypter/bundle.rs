@@ -0,0 +1,141 @@
+use concrete_commons::{
+    dispersion::{DispersionParameter, StandardDev},
+    key_kinds::BinaryKeyKind,
+    parameters::{DecompositionBaseLog, DecompositionLevelCount, GlweDimension, PolynomialSize},
+};
+use concrete_core::crypto::secret::GlweSecretKey;
+use serde::{Deserialize, Serialize};
+
+use crate::{EncryptedKeyBit, ParameterError, ParameterSet, SystemParameters, Torus};
+
+/// The encrypted symmetric key a client uploads to the server, along with the parameter set
+/// and FHE parameters it was encrypted for.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct KeyBundle {
+    parameters: String,
+    glwe_dimension: usize,
+    polynomial_size: usize,
+    base_log: usize,
+    level_count: usize,
+    std_dev: f64,
+    key: Vec<EncryptedKeyBit>,
+}
+
+impl KeyBundle {
+    /// Encrypts the clear symmetric key `key` under the FHE secret key `sk`, with the FHE
+    /// parameters of `params`. Fails if the key or the secret key do not match the preset.
+    pub fn new(
+        params: &SystemParameters,
+        key: &[bool],
+        sk: &GlweSecretKey<BinaryKeyKind, Vec<Torus>>,
+    ) -> Result<Self, ParameterError> {
+        Self::from_parameter_set(&params.parameter_set(), key, sk)
+    }
+
+    /// Encrypts the clear symmetric key `key` under the FHE secret key `sk`, with the FHE
+    /// parameters of `set`. Fails if the key or the secret key do not match the set.
+    pub fn from_parameter_set(
+        set: &ParameterSet,
+        key: &[bool],
+        sk: &GlweSecretKey<BinaryKeyKind, Vec<Torus>>,
+    ) -> Result<Self, ParameterError> {
+        let (glwe_dimension, poly_size, base_log, level, std_dev) = set.fhe_parameters();
+        if (sk.key_size(), sk.polynomial_size()) != (glwe_dimension, poly_size) {
+            return Err(ParameterError::Fhe(format!(
+                "the FHE secret key does not match the parameters of {}",
+                set.name
+            )));
+        }
+        if key.len() != set.key_size {
+            return Err(ParameterError::KeyLength {
+                expected: set.key_size,
+                found: key.len(),
+            });
+        }
+        Ok(Self {
+            parameters: set.name.clone(),
+            glwe_dimension: glwe_dimension.0,
+            polynomial_size: poly_size.0,
+            base_log: base_log.0,
+            level_count: level.0,
+            std_dev: std_dev.get_standard_dev(),
+            key: EncryptedKeyBit::encrypt_key(key, sk, level, base_log, std_dev),
+        })
+    }
+
+    /// Name of the parameter set the key was encrypted for.
+    pub fn parameters_name(&self) -> &str {
+        &self.parameters
+    }
+
+    pub fn system_parameters(&self) -> Option<SystemParameters> {
+        SystemParameters::from_name(&self.parameters)
+    }
+
+    pub fn fhe_parameters(
+        &self,
+    ) -> (
+        GlweDimension,
+        PolynomialSize,
+        DecompositionBaseLog,
+        DecompositionLevelCount,
+        StandardDev,
+    ) {
+        (
+            GlweDimension(self.glwe_dimension),
+            PolynomialSize(self.polynomial_size),
+            DecompositionBaseLog(self.base_log),
+            DecompositionLevelCount(self.level_count),
+            StandardDev::from_standard_dev(self.std_dev),
+        )
+    }
+
+    /// Checks that the bundle was made for `set`: a key of the right length, encrypted with
+    /// the FHE parameters of the set, as announced by the bundle and as found in every key bit.
+    pub fn validate(&self, set: &ParameterSet) -> Result<(), ParameterError> {
+        if self.key.len() != set.key_size {
+            return Err(ParameterError::KeyLength {
+                expected: set.key_size,
+                found: self.key.len(),
+            });
+        }
+        let (glwe_dimension, poly_size, base_log, level, std_dev) = set.fhe_parameters();
+        let announced = self.fhe_parameters();
+        if (announced.0, announced.1, announced.2, announced.3)
+            != (glwe_dimension, poly_size, base_log, level)
+            || announced.4.get_standard_dev() != std_dev.get_standard_dev()
+        {
+            return Err(ParameterError::Fhe(format!(
+                "the key was encrypted with other FHE parameters than {}",
+                set.name
+            )));
+        }
+        if let Some(i) = self
+            .key
+            .iter()
+            .position(|bit| bit.fhe_parameters() != (glwe_dimension, poly_size, base_log, level))
+        {
+            return Err(ParameterError::Fhe(format!(
+                "key bit {} does not match the FHE parameters of {}",
+                i, set.name
+            )));
+        }
+        Ok(())
+    }
+
+    pub fn key(&self) -> &[EncryptedKeyBit] {
+        &self.key
+    }
+
+    pub fn into_key(self) -> Vec<EncryptedKeyBit> {
+        self.key
+    }
+
+    pub fn serialize(&self) -> Vec<u8> {
+        bincode::serialize(self).unwrap()
+    }
+
+    pub fn deserialize(bytes: &[u8]) -> bincode::Result<Self> {
+        bincode::deserialize(bytes)
+    }
+}
@@ -0,0 +1,245 @@
+use std::{convert::TryFrom, error::Error, fmt, ops::Range};
+
+use crate::Nonce;
+use serde::{Deserialize, Serialize};
+
+/// A packet of a FiLIP ciphertext stream. Besides the packed ciphertext bits, it carries the
+/// nonce of its stream and the keystream offset of its first bit, so that it can be
+/// transciphered independently of the other packets. Deserialized packets go through
+/// `Ciphertext::from_parts`, so a malformed packet is rejected instead of panicking later.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "RawCiphertext")]
+pub struct Ciphertext {
+    nonce: Nonce,
+    offset: u64,
+    len: usize,
+    bits: Vec<u8>,
+}
+
+/// Fields of a `Ciphertext` as read from the wire, before validation.
+#[derive(Deserialize)]
+struct RawCiphertext {
+    nonce: Nonce,
+    offset: u64,
+    len: usize,
+    bits: Vec<u8>,
+}
+
+impl TryFrom<RawCiphertext> for Ciphertext {
+    type Error = PacketError;
+
+    fn try_from(raw: RawCiphertext) -> Result<Self, Self::Error> {
+        Self::from_parts(raw.nonce, raw.offset, raw.len, raw.bits)
+    }
+}
+
+impl Ciphertext {
+    /// Packs `bits` most significant bit first.
+    pub fn new(nonce: Nonce, offset: u64, bits: &[bool]) -> Self {
+        let mut packed = vec![0_u8; (bits.len() + 7) / 8];
+        for (i, _) in bits.iter().enumerate().filter(|(_, &b)| b) {
+            packed[i / 8] |= 0x80 >> (i % 8);
+        }
+        Self::from_parts(nonce, offset, bits.len(), packed).unwrap()
+    }
+
+    /// Packet of `len` bits packed in `bits`, most significant bit first. Fails if `bits` does
+    /// not hold exactly `len` bits, or if the packet ends beyond the last keystream position.
+    pub fn from_parts(
+        nonce: Nonce,
+        offset: u64,
+        len: usize,
+        bits: Vec<u8>,
+    ) -> Result<Self, PacketError> {
+        if bits.len() != (len + 7) / 8 {
+            return Err(PacketError::Truncated {
+                len,
+                bytes: bits.len(),
+            });
+        }
+        if offset.checked_add(len as u64).is_none() {
+            return Err(PacketError::OutOfRange { offset, len });
+        }
+        Ok(Self {
+            nonce,
+            offset,
+            len,
+            bits,
+        })
+    }
+
+    pub fn nonce(&self) -> Nonce {
+        self.nonce
+    }
+
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Range of keystream positions covered by the packet.
+    pub fn range(&self) -> Range<u64> {
+        self.offset..self.offset + self.len as u64
+    }
+
+    pub fn bits(&self) -> Vec<bool> {
+        (0..self.len)
+            .map(|i| self.bits[i / 8] & (0x80 >> (i % 8)) != 0)
+            .collect()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PacketError {
+    /// The packet belongs to another stream.
+    NonceMismatch { expected: Nonce, found: Nonce },
+    /// Part of the packet's keystream range has already been used.
+    Overlap { range: Range<u64> },
+    /// The packed bits do not hold the `len` bits announced by the packet.
+    Truncated { len: usize, bytes: usize },
+    /// The packet ends beyond the last keystream position.
+    OutOfRange { offset: u64, len: usize },
+    /// The output buffer cannot hold the packet.
+    BufferTooSmall { len: usize, available: usize },
+}
+
+impl fmt::Display for PacketError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NonceMismatch { expected, found } => write!(
+                f,
+                "packet nonce {:#x} does not match the stream nonce {:#x}",
+                found, expected
+            ),
+            Self::Overlap { range } => write!(
+                f,
+                "keystream range {}..{} overlaps an already consumed range",
+                range.start, range.end
+            ),
+            Self::Truncated { len, bytes } => {
+                write!(f, "{} bytes cannot hold a packet of {} bits", bytes, len)
+            }
+            Self::OutOfRange { offset, len } => write!(
+                f,
+                "a packet of {} bits at offset {} ends beyond the keystream",
+                len, offset
+            ),
+            Self::BufferTooSmall { len, available } => write!(
+                f,
+                "a packet of {} bits does not fit in a buffer of {} bits",
+                len, available
+            ),
+        }
+    }
+}
+
+impl Error for PacketError {}
+
+/// Sorted, disjoint keystream ranges already used by an encrypter.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct ConsumedRanges(Vec<Range<u64>>);
+
+impl ConsumedRanges {
+    pub(crate) fn clear(&mut self) {
+        self.0.clear();
+    }
+
+    /// Fails if `range` overlaps a range consumed before.
+    pub(crate) fn check(&self, range: &Range<u64>) -> Result<(), PacketError> {
+        let i = self.0.partition_point(|r| r.end <= range.start);
+        if range.start < range.end && self.0.get(i).map_or(false, |r| r.start < range.end) {
+            return Err(PacketError::Overlap {
+                range: range.clone(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Records `range` as consumed, unless it overlaps a range consumed before.
+    pub(crate) fn insert(&mut self, range: Range<u64>) -> Result<(), PacketError> {
+        self.check(&range)?;
+        if range.start == range.end {
+            return Ok(());
+        }
+        let i = self.0.partition_point(|r| r.end <= range.start);
+
+        let merge_previous = i > 0 && self.0[i - 1].end == range.start;
+        let merge_next = self.0.get(i).map_or(false, |r| r.start == range.end);
+        match (merge_previous, merge_next) {
+            (true, true) => {
+                let next = self.0.remove(i);
+                self.0[i - 1].end = next.end;
+            }
+            (true, false) => self.0[i - 1].end = range.end,
+            (false, true) => self.0[i].start = range.start,
+            (false, false) => self.0.insert(i, range),
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn packing() {
+        let bits = [
+            true, false, true, true, false, false, false, true, true, false,
+        ];
+        let packet = Ciphertext::new(3, 17, &bits);
+        assert_eq!(packet.bits, vec![0b1011_0001, 0b1000_0000]);
+        assert_eq!(packet.bits(), bits.to_vec());
+        assert_eq!(packet.range(), 17..27);
+    }
+
+    #[test]
+    fn malformed() {
+        assert_eq!(
+            Ciphertext::from_parts(3, 17, 17, vec![0; 2]),
+            Err(PacketError::Truncated { len: 17, bytes: 2 })
+        );
+        assert_eq!(
+            Ciphertext::from_parts(3, u64::MAX - 4, 8, vec![0]),
+            Err(PacketError::OutOfRange {
+                offset: u64::MAX - 4,
+                len: 8
+            })
+        );
+        assert!(Ciphertext::from_parts(3, 17, 16, vec![0; 2]).is_ok());
+
+        // A packet announcing more bits than it carries is rejected on deserialization.
+        let mut packet = Ciphertext::new(3, 17, &[true; 10]);
+        packet.len = 100;
+        let bytes = bincode::serialize(&packet).unwrap();
+        assert!(bincode::deserialize::<Ciphertext>(&bytes).is_err());
+        packet.len = 10;
+        let bytes = bincode::serialize(&packet).unwrap();
+        assert_eq!(bincode::deserialize::<Ciphertext>(&bytes).unwrap(), packet);
+    }
+
+    #[test]
+    fn consumed_ranges() {
+        let mut consumed = ConsumedRanges::default();
+        assert_eq!(consumed.insert(10..20), Ok(()));
+        assert_eq!(consumed.insert(30..40), Ok(()));
+        assert_eq!(consumed.insert(0..5), Ok(()));
+        assert_eq!(consumed.insert(20..30), Ok(()));
+        assert_eq!(consumed.0, vec![0..5, 10..40]);
+
+        assert!(consumed.insert(10..20).is_err());
+        assert!(consumed.insert(4..6).is_err());
+        assert!(consumed.insert(39..41).is_err());
+        assert!(consumed.check(&(35..36)).is_err());
+        assert_eq!(consumed.check(&(40..50)), Ok(()));
+        assert_eq!(consumed.insert(5..10), Ok(()));
+        assert_eq!(consumed.0, vec![0..40]);
+    }
+}
@@ -0,0 +1,254 @@
+//! Checks the keystream against the known-answer test files of `tests/kat`, and the reference
+//! mode against the reference implementation. The files were generated by this crate, so they
+//! catch regressions of its own keystream; `PrngType::Reference` is instead checked against the
+//! code of the reference implementation published with the transciphering paper, kept verbatim
+//! in `reference` below.
+
+use crate::{prng::PrngType, Encrypter, SystemParameters};
+use strum::IntoEnumIterator;
+
+fn from_hex(hex: &str) -> Vec<bool> {
+    (0..hex.len() / 2)
+        .map(|i| u8::from_str_radix(&hex[2 * i..2 * i + 2], 16).unwrap())
+        .flat_map(|byte| (0..8).rev().map(move |i| (byte >> i) & 1 == 1))
+        .collect()
+}
+
+fn prng(name: &str) -> PrngType {
+    match name {
+        "AesCtr" => PrngType::AesCtr,
+        "Shake128" => PrngType::Shake128,
+        "ChaCha20" => PrngType::ChaCha20,
+        _ => panic!("unknown PRNG {}", name),
+    }
+}
+
+fn check(params: &SystemParameters, kat: &str) {
+    let mut key = Vec::new();
+    let mut nonce = 0;
+    let mut encrypter: Option<Encrypter<bool>> = None;
+    let mut keystream = Vec::new();
+
+    for line in kat.lines().filter(|l| !l.is_empty() && !l.starts_with('#')) {
+        let (field, value) = line.split_once(" = ").unwrap();
+        let mut field = field.split(' ');
+        match (field.next().unwrap(), field.next()) {
+            ("parameters", None) => assert_eq!(value, params.name()),
+            ("key", None) => key = from_hex(value),
+            ("nonce", None) => nonce = u128::from_str_radix(value, 16).unwrap(),
+            ("prng", None) => {
+                let mut e = Encrypter::from_clear_key(key.clone(), params, nonce).unwrap();
+                e.set_prng(prng(value));
+                encrypter = Some(e);
+                keystream.clear();
+            }
+            ("subset", Some(round)) => {
+                let encrypter = encrypter.as_mut().unwrap();
+                let round = round.parse::<usize>().unwrap();
+                while keystream.len() <= round {
+                    keystream.push(encrypter.stream());
+                }
+                let indices = value
+                    .split(',')
+                    .map(|i| i.parse::<usize>().unwrap())
+                    .collect::<Vec<_>>();
+                assert_eq!(encrypter.key.subset().0, indices);
+            }
+            ("whitening", Some(_)) => {
+                assert_eq!(encrypter.as_ref().unwrap().key.subset().1, from_hex(value));
+            }
+            ("keystream", None) => {
+                let expected = from_hex(value);
+                let encrypter = encrypter.as_mut().unwrap();
+                while keystream.len() < expected.len() {
+                    keystream.push(encrypter.stream());
+                }
+                assert_eq!(keystream, expected);
+            }
+            _ => panic!("unexpected line: {}", line),
+        }
+    }
+}
+
+#[test]
+fn n144() {
+    check(
+        &SystemParameters::n144,
+        include_str!("../../tests/kat/FiLIP_144.kat"),
+    );
+}
+
+#[test]
+fn n1216() {
+    check(
+        &SystemParameters::n1216,
+        include_str!("../../tests/kat/FiLIP_1216.kat"),
+    );
+}
+
+#[test]
+fn n1280() {
+    check(
+        &SystemParameters::n1280,
+        include_str!("../../tests/kat/FiLIP_1280.kat"),
+    );
+}
+
+/// Subset selection and filters of the reference implementation, as published with the
+/// transciphering paper: the oracle of the reference mode.
+mod reference {
+    use crate::multiplexer::Multiplexer;
+    use concrete_core::math::random::RandomGenerator;
+
+    pub struct SymmetricKey {
+        key: Vec<bool>,
+        indices: Vec<usize>,
+        whitening: Vec<bool>,
+        rng: RandomGenerator,
+    }
+
+    impl SymmetricKey {
+        pub fn new(key: Vec<bool>, n: usize, seed: u128) -> Self {
+            let indices = (0..key.len()).collect();
+            Self {
+                key,
+                indices,
+                whitening: vec![false; n],
+                rng: RandomGenerator::new(Some(seed)),
+            }
+        }
+
+        pub fn random_whitened_subset(&mut self) -> Vec<bool> {
+            let n = self.whitening.len();
+            let key_len = self.key.len();
+            for i in 0..n {
+                let j = gen_range(&mut self.rng, i, key_len);
+                self.indices.swap(i, j);
+            }
+            for w in self.whitening.iter_mut() {
+                *w = self.rng.random_uniform_binary::<u8>() == 1;
+            }
+            self.indices[..n]
+                .iter()
+                .zip(self.whitening.iter())
+                .map(|(&i, &w)| self.key[i] ^ w)
+                .collect()
+        }
+    }
+
+    fn gen_range(rng: &mut RandomGenerator, min: usize, max: usize) -> usize {
+        if min > max {
+            panic!("`min` of range must be less than or equal to `max`");
+        }
+        let bit_len = ((max - min) as f64).log2().floor() as usize;
+        let mut a = (min as u32 + rng.random_uniform_n_lsb::<u32>(bit_len)) as usize;
+        while a >= max {
+            a = (min as u32 + rng.random_uniform_n_lsb::<u32>(bit_len)) as usize;
+        }
+        a
+    }
+
+    pub fn dsm<M: Multiplexer>(x: &[M], m: &[usize]) -> M::Bit {
+        let mut j = 0;
+        let mut returnValue = x[j].as_bit();
+        j += 1;
+        for x_j in x.iter().take(m[0]).skip(1) {
+            returnValue ^= x_j.as_bit();
+            j += 1;
+        }
+
+        for (i, &m_i) in m.iter().enumerate().skip(1) {
+            for _ in 0..m_i {
+                let mut temp = x[j].as_bit();
+                j += 1;
+                for _ in 0..i {
+                    temp &= x[j].clone();
+                    j += 1;
+                }
+                returnValue ^= temp;
+            }
+        }
+        returnValue
+    }
+
+    fn threshold<M: Multiplexer>(x: &[M], d: usize) -> M::Bit {
+        let mut acc = vec![!x[0].as_bit(), x[0].as_bit()];
+        for (i, x_i) in x.iter().enumerate().skip(1) {
+            acc.push(x_i.clone() & acc.last().unwrap().clone());
+            for j in (1..acc.len() - 1).rev() {
+                acc[j] = x_i.mux(&acc[j - 1], &acc[j]);
+            }
+            if i < d - 1 {
+                acc[0] = !x_i.clone() & acc.first().unwrap().clone();
+            } else {
+                acc.remove(0);
+                if i > d - 1 {
+                    let last = acc.pop().unwrap();
+                    *acc.last_mut().unwrap() ^= last;
+                }
+            }
+        }
+        acc[0].clone()
+    }
+
+    pub fn xor_thr<M: Multiplexer>(x: &[M], k: usize, d: usize) -> M::Bit {
+        let mut returnValue = x[0].as_bit();
+
+        for v in x[1..k].iter() {
+            returnValue ^= v.as_bit();
+        }
+        returnValue ^ threshold(&x[k..], d)
+    }
+}
+
+#[test]
+fn reference_mode() {
+    // The presets as defined by the reference implementation.
+    let filters: [(&SystemParameters, fn(&[bool]) -> bool); 3] = [
+        (&SystemParameters::n1216, |x| {
+            reference::dsm(x, &[128, 64, 0, 80, 0, 0, 0, 80])
+        }),
+        (&SystemParameters::n1280, |x| {
+            reference::dsm(x, &[128, 64, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 64])
+        }),
+        (&SystemParameters::n144, |x| reference::xor_thr(x, 81, 32)),
+    ];
+    assert_eq!(filters.len(), SystemParameters::iter().count());
+    for (params, filter) in filters.iter() {
+        let parameters = params.parameters();
+        let key = (0..parameters.key_size())
+            .map(|i| i % 5 < 2)
+            .collect::<Vec<_>>();
+        for nonce in 0..2 {
+            let mut expected = reference::SymmetricKey::new(key.clone(), parameters.n(), nonce);
+            let mut encrypter = Encrypter::from_clear_key(key.clone(), params, nonce).unwrap();
+            encrypter.set_prng(PrngType::Reference);
+            for _ in 0..8 {
+                let subset = expected.random_whitened_subset();
+                assert_eq!(encrypter.stream(), filter(&subset), "{}", params.name());
+                let (indices, whitening) = encrypter.key.subset();
+                let whitened = indices
+                    .iter()
+                    .zip(whitening)
+                    .map(|(&i, w)| key[i] ^ w)
+                    .collect::<Vec<_>>();
+                assert_eq!(whitened, subset);
+            }
+        }
+    }
+
+    // Seeking back replays the reference generator.
+    let params = SystemParameters::n144;
+    let key = (0..params.parameters().key_size())
+        .map(|i| i % 3 == 0)
+        .collect::<Vec<_>>();
+    let mut expected = reference::SymmetricKey::new(key.clone(), params.parameters().n(), 42);
+    let expected = (0..4)
+        .map(|_| expected.random_whitened_subset())
+        .collect::<Vec<_>>();
+    let mut encrypter = Encrypter::from_clear_key(key, &params, 42).unwrap();
+    encrypter.set_prng(PrngType::Reference);
+    encrypter.skip(3);
+    encrypter.seek(1);
+    assert_eq!(encrypter.stream(), reference::xor_thr(&expected[1], 81, 32));
+}
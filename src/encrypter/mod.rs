@@ -1,13 +1,26 @@
+mod bundle;
+mod ciphertext;
+#[cfg(test)]
+mod kat;
+mod parameter_set;
 mod parameters;
+mod stream;
 
 use std::{
+    convert::TryFrom,
     env, fs,
     io::{stdout, Write},
     path::Path,
     time::Instant,
 };
 
-use crate::{filter::Filter, multiplexer::Multiplexer, symmetric_key::SymmetricKey, Bit, Torus};
+use crate::{
+    filter::{Filter, FilterExpression},
+    multiplexer::Multiplexer,
+    prng::{KeystreamPrng, PrngType},
+    symmetric_key::{Nonce, SymmetricKey},
+    Bit, EncryptedKeyBit, Torus,
+};
 use concrete_commons::{
     dispersion::StandardDev,
     key_kinds::BinaryKeyKind,
@@ -15,29 +28,41 @@ use concrete_commons::{
 };
 use concrete_core::{crypto::secret::GlweSecretKey, math::random::RandomGenerator};
 
+pub use bundle::KeyBundle;
+use ciphertext::ConsumedRanges;
+pub use ciphertext::{Ciphertext, PacketError};
 use crossterm::{cursor, QueueableCommand};
+pub use parameter_set::{ParameterError, ParameterSet};
 pub use parameters::*;
+pub use stream::{KeystreamReader, KeystreamWriter};
 
 pub struct Encrypter<M: Multiplexer> {
     key: SymmetricKey<M>,
     filter: Filter,
+    consumed: ConsumedRanges,
 }
 
 impl<M: Multiplexer> Encrypter<M> {
+    /// Draws a fresh symmetric key for `params`, and builds the encrypters of both sides over
+    /// it. Fails if the preset does not pass the checks of `from_parameter_set`.
     pub fn new<U: Multiplexer>(
         params: &SystemParameters,
+        nonce: Nonce,
         sk: Option<&GlweSecretKey<BinaryKeyKind, Vec<Torus>>>,
         decomp_level: Option<DecompositionLevelCount>,
         decomp_base_log: Option<DecompositionBaseLog>,
         noise_parameters: Option<StandardDev>,
-    ) -> (Self, Encrypter<U>) {
+    ) -> Result<(Self, Encrypter<U>), ParameterError> {
+        let set = params.parameter_set();
+        set.validate()?;
+        set.ensure_secure(false)?;
         let mut generator = RandomGenerator::new(None);
-        let seed = generator.random_uniform();
 
         let Parameters {
             n,
             key_size,
             filter,
+            prng,
         } = params.parameters();
 
         let mut key1 = vec![
@@ -69,16 +94,78 @@ impl<M: Multiplexer> Encrypter<M> {
             &mut generator,
             params,
         );
-        (
-            Self {
-                key: SymmetricKey::new(key1, n, seed),
-                filter: filter.clone(),
-            },
-            Encrypter::<U> {
-                key: SymmetricKey::new(key2, n, seed),
-                filter,
-            },
-        )
+        Ok((
+            Self::with_filter(key1, n, filter.clone(), prng, nonce)?,
+            Encrypter::<U>::with_filter(key2, n, filter, prng, nonce)?,
+        ))
+    }
+
+    /// Builds an encrypter from an existing symmetric key, so that each party can build its
+    /// own side independently. Fails if the key does not match the preset.
+    pub fn from_key(
+        key: Vec<M>,
+        params: &SystemParameters,
+        nonce: Nonce,
+    ) -> Result<Self, ParameterError> {
+        Self::from_parameter_set(key, &params.parameter_set(), nonce, false)
+    }
+
+    /// Builds an encrypter from an existing symmetric key, for a parameter set that may have
+    /// been loaded from a file. Fails if the key does not match the set, or if the set is
+    /// estimated below 80 bits of security and `allow_insecure` is not set, see
+    /// `ParameterSet::ensure_secure`.
+    pub fn from_parameter_set(
+        key: Vec<M>,
+        set: &ParameterSet,
+        nonce: Nonce,
+        allow_insecure: bool,
+    ) -> Result<Self, ParameterError> {
+        if key.len() != set.key_size {
+            return Err(ParameterError::KeyLength {
+                expected: set.key_size,
+                found: key.len(),
+            });
+        }
+        set.validate()?;
+        set.ensure_secure(allow_insecure)?;
+        Self::with_filter(key, set.n, set.filter.clone(), set.prng, nonce)
+    }
+
+    /// Builds an encrypter around a custom filter, which must be well formed and read `n`
+    /// inputs out of the key.
+    pub fn with_filter(
+        key: Vec<M>,
+        n: usize,
+        filter: Filter,
+        prng: PrngType,
+        nonce: Nonce,
+    ) -> Result<Self, ParameterError> {
+        if n == 0 || n > key.len() {
+            return Err(ParameterError::KeySize {
+                n,
+                key_size: key.len(),
+            });
+        }
+        filter.validate(n)?;
+        Ok(Self {
+            key: SymmetricKey::new(key, n, prng, nonce),
+            filter,
+            consumed: ConsumedRanges::default(),
+        })
+    }
+
+    /// Builds an encrypter around a custom filter and a custom generator of the subset
+    /// selection and whitening, restarted under `nonce`. Both sides must use the same one.
+    pub fn with_generator(
+        key: Vec<M>,
+        n: usize,
+        filter: Filter,
+        generator: Box<dyn KeystreamPrng + Send>,
+        nonce: Nonce,
+    ) -> Result<Self, ParameterError> {
+        let mut returnValue = Self::with_filter(key, n, filter, PrngType::AesCtr, nonce)?;
+        returnValue.key.set_generator(generator);
+        Ok(returnValue)
     }
 
     fn key_gen<U: Multiplexer>(
@@ -222,26 +309,228 @@ impl<M: Multiplexer> Encrypter<M> {
         }
     }
 
+    /// The symmetric key, in the clear or encrypted depending on the side.
+    pub fn key(&self) -> &[M] {
+        self.key.key()
+    }
+
+    pub fn nonce(&self) -> Nonce {
+        self.key.nonce()
+    }
+
+    /// Restarts the keystream under `nonce`, keeping the same symmetric key.
+    pub fn set_nonce(&mut self, nonce: Nonce) {
+        self.key.set_nonce(nonce);
+        self.consumed.clear();
+    }
+
+    /// Switches the generator of the subset selection and whitening, restarting the keystream.
+    /// Together with `from_key`, this gives a fully deterministic keystream.
+    pub fn set_prng(&mut self, prng: PrngType) {
+        self.key.set_prng(prng);
+        self.consumed.clear();
+    }
+
+    /// Switches to a custom generator of the subset selection and whitening, restarting the
+    /// keystream.
+    pub fn set_generator(&mut self, generator: Box<dyn KeystreamPrng + Send>) {
+        self.key.set_generator(generator);
+        self.consumed.clear();
+    }
+
+    /// Index of the next keystream bit.
+    pub fn position(&self) -> u64 {
+        self.key.position()
+    }
+
+    /// Moves the keystream to bit `position` without evaluating the filter on the bits in
+    /// between, so that a server can transcipher any part of a stream at PRNG cost only. Going
+    /// back replays the PRNG from the start of the stream, in time linear in `position`.
+    pub fn seek(&mut self, position: u64) {
+        self.key.seek(position);
+    }
+
+    /// Discards the next `n` keystream bits without evaluating the filter.
+    pub fn skip(&mut self, n: u64) {
+        self.key.skip(n);
+    }
+
     fn stream(&mut self) -> M::Bit {
         let key_round = self.key.random_whitened_subset();
         self.filter.call(&key_round)
     }
 
+    /// Evaluates the next keystream bit sub-filter by sub-filter, see `Filter::stages`, to see
+    /// how the noise builds up.
+    pub fn stages(&mut self) -> Vec<(FilterExpression, M::Bit)> {
+        let key_round = self.key.random_whitened_subset();
+        self.filter.stages(&key_round)
+    }
+
+    /// Encrypts `message` at the current keystream position. The keystream range is recorded
+    /// as used, as by `encrypt_packet`.
+    ///
+    /// Panics if part of that range has already been used for encryption, see `encrypt_packet`
+    /// to get an error instead.
     pub fn encrypt(&mut self, res: &mut [M::Bit], message: &[bool]) {
-        for (c, m) in res.iter_mut().zip(message.iter()) {
-            *c = self.stream();
-            if *m {
-                c.not_inplace();
+        self.consume(res.len().min(message.len()))
+            .unwrap_or_else(|error| panic!("{}", error));
+        self.apply_stream(res, message);
+    }
+
+    pub fn decrypt(&mut self, res: &mut [M::Bit], ciphertext: &[bool]) {
+        self.apply_stream(res, ciphertext);
+    }
+
+    /// Xors the next keystream bits into `bits`, without recording them as used.
+    fn apply_stream(&mut self, res: &mut [M::Bit], bits: &[bool]) {
+        for (r, b) in res.iter_mut().zip(bits.iter()) {
+            *r = self.stream();
+            if *b {
+                r.not_inplace();
             }
         }
     }
 
-    pub fn decrypt(&mut self, res: &mut [M::Bit], ciphertext: &[bool]) {
-        for (d, c) in res.iter_mut().zip(ciphertext.iter()) {
-            *d = self.stream();
-            if *c {
-                d.not_inplace();
+    /// Records the next `len` keystream bits as used for encryption. Fails if they run beyond
+    /// the last keystream position, or if part of them has already been used.
+    fn consume(&mut self, len: usize) -> Result<(), PacketError> {
+        let offset = self.position();
+        let end = offset
+            .checked_add(len as u64)
+            .ok_or(PacketError::OutOfRange { offset, len })?;
+        self.consumed.insert(offset..end)
+    }
+
+    /// Transciphers `ciphertext` byte by byte. Each byte gives a group of eight bits, most
+    /// significant bit first, matching the bit order of `Encrypter::encrypt_bytes`.
+    pub fn decrypt_bytes(&mut self, ciphertext: &[u8]) -> Vec<[M::Bit; 8]> {
+        ciphertext
+            .iter()
+            .map(|c| {
+                let byte = (0..8)
+                    .rev()
+                    .map(|i| {
+                        let mut d = self.stream();
+                        if (c >> i) & 1 == 1 {
+                            d.not_inplace();
+                        }
+                        d
+                    })
+                    .collect::<Vec<_>>();
+                <[M::Bit; 8]>::try_from(byte).unwrap_or_else(|_| unreachable!())
+            })
+            .collect()
+    }
+
+    /// Encrypts `message` at the current keystream position into a self-describing packet.
+    /// Fails if that part of the keystream has already been used for encryption, by a packet,
+    /// `encrypt` or `encrypt_bytes`, or if it runs beyond the last keystream position.
+    pub fn encrypt_packet(&mut self, message: &[bool]) -> Result<Ciphertext, PacketError>
+    where
+        M: Multiplexer<Bit = bool>,
+    {
+        let offset = self.position();
+        self.consume(message.len())?;
+        let mut ciphertext = vec![false; message.len()];
+        self.apply_stream(&mut ciphertext, message);
+        Ok(Ciphertext::new(self.nonce(), offset, &ciphertext))
+    }
+
+    /// Transciphers `packet` into `res`, whatever order packets arrive in. Packets from another
+    /// stream, packets overlapping a range that was already transciphered, and packets larger
+    /// than `res` are rejected, and leave the encrypter untouched.
+    ///
+    /// A packet behind the current position replays the PRNG from the start of the stream, see
+    /// `seek`: packets arriving in decreasing order cost time quadratic in their number.
+    pub fn decrypt_packet(
+        &mut self,
+        res: &mut [M::Bit],
+        packet: &Ciphertext,
+    ) -> Result<(), PacketError> {
+        if packet.nonce() != self.nonce() {
+            return Err(PacketError::NonceMismatch {
+                expected: self.nonce(),
+                found: packet.nonce(),
+            });
+        }
+        if res.len() < packet.len() {
+            return Err(PacketError::BufferTooSmall {
+                len: packet.len(),
+                available: res.len(),
+            });
+        }
+        self.consumed.check(&packet.range())?;
+        self.seek(packet.offset());
+        self.apply_stream(&mut res[..packet.len()], &packet.bits());
+        self.consumed.insert(packet.range())
+    }
+}
+
+impl Encrypter<bool> {
+    /// Draws a fresh clear symmetric key for `params`.
+    pub fn generate_key(params: &SystemParameters) -> Vec<bool> {
+        let mut generator = RandomGenerator::new(None);
+        (0..params.parameters().key_size())
+            .map(|_| generator.random_uniform_binary::<u8>() == 1)
+            .collect()
+    }
+
+    /// Client side encrypter, built from the clear symmetric key.
+    pub fn from_clear_key(
+        key_bits: Vec<bool>,
+        params: &SystemParameters,
+        nonce: Nonce,
+    ) -> Result<Self, ParameterError> {
+        Self::from_key(key_bits, params, nonce)
+    }
+
+    /// Encrypts `message` byte by byte, most significant bit first. The keystream range is
+    /// recorded as used, and must not have been used for encryption before, as in `encrypt`.
+    pub fn encrypt_bytes(&mut self, message: &[u8]) -> Vec<u8> {
+        self.consume(8 * message.len())
+            .unwrap_or_else(|error| panic!("{}", error));
+        let mut ciphertext = message.to_vec();
+        self.apply_keystream(&mut ciphertext);
+        ciphertext
+    }
+
+    /// Xors the keystream into `data`, most significant bit first. In the clear, this both
+    /// encrypts and decrypts.
+    fn apply_keystream(&mut self, data: &mut [u8]) {
+        for byte in data.iter_mut() {
+            for i in (0..8).rev() {
+                if self.stream() {
+                    *byte ^= 1 << i;
+                }
             }
         }
     }
 }
+
+impl Encrypter<EncryptedKeyBit> {
+    /// Server side encrypter, built from the encrypted symmetric key only: neither the FHE
+    /// secret key nor the clear symmetric key is needed.
+    pub fn from_encrypted_key(
+        key: Vec<EncryptedKeyBit>,
+        params: &SystemParameters,
+        nonce: Nonce,
+    ) -> Result<Self, ParameterError> {
+        Self::from_key(key, params, nonce)
+    }
+
+    /// Server side encrypter, built from a key bundle uploaded by the client. Fails if the
+    /// bundle was made for an unknown parameter set, or does not match it, see
+    /// `KeyBundle::validate`.
+    pub fn from_bundle(bundle: KeyBundle, nonce: Nonce) -> Result<Self, ParameterError> {
+        let params = bundle.system_parameters().ok_or_else(|| {
+            ParameterError::Format(format!(
+                "unknown parameter set {}",
+                bundle.parameters_name()
+            ))
+        })?;
+        let set = params.parameter_set();
+        bundle.validate(&set)?;
+        Self::from_parameter_set(bundle.into_key(), &set, nonce, false)
+    }
+}
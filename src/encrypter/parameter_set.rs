@@ -0,0 +1,454 @@
+use std::{env, error::Error, fmt, fs, path::Path};
+
+use crate::{
+    analysis::SecurityEstimate,
+    bit::{Bit, CountingBit, SymbolicBit},
+    filter::{Filter, FilterError},
+    multiplexer::{CountingKeyBit, OperationCount, SymbolicKeyBit},
+    noise::{self, NoiseEstimate, NoiseModel},
+    prng::PrngType,
+    symmetric_key::Nonce,
+    Torus,
+};
+use concrete_commons::{
+    dispersion::StandardDev,
+    key_kinds::BinaryKeyKind,
+    parameters::{DecompositionBaseLog, DecompositionLevelCount, GlweDimension, PolynomialSize},
+};
+use concrete_core::crypto::secret::{generators::SecretRandomGenerator, GlweSecretKey};
+use serde::{Deserialize, Serialize};
+
+use super::{Encrypter, Parameters};
+
+/// Full description of a FiLIP instance and of the FHE parameters of its transciphering, which
+/// can be loaded from a TOML or JSON file.
+///
+/// ```toml
+/// name = "FiLIP 144"
+/// n = 144
+/// key_size = 16384
+/// prng = "AesCtr"
+/// glwe_dimension = 3
+/// polynomial_size = 512
+/// base_log = 15
+/// level_count = 1
+/// std_dev = 3.53e-10
+///
+/// [filter]
+/// parameters = [81, 32]
+/// category = "XorThr"
+/// ```
+// The filter comes last, as TOML writes tables after the plain values.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ParameterSet {
+    pub name: String,
+    /// Number of key bits fed to the filter at each keystream bit.
+    pub n: usize,
+    pub key_size: usize,
+    pub prng: PrngType,
+    pub glwe_dimension: usize,
+    pub polynomial_size: usize,
+    pub base_log: usize,
+    pub level_count: usize,
+    /// Standard deviation of the encryption noise, on the torus.
+    pub std_dev: f64,
+    pub filter: Filter,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ParameterError {
+    Filter(FilterError),
+    KeySize {
+        n: usize,
+        key_size: usize,
+    },
+    Fhe(String),
+    Format(String),
+    /// A symmetric key of the wrong length was given for the set.
+    KeyLength {
+        expected: usize,
+        found: usize,
+    },
+    /// The set is estimated below 80 bits of security, and the caller did not allow it.
+    Insecure {
+        name: String,
+        bits: f64,
+    },
+    /// The filter of the set is too large for its security to be estimated, and the caller did
+    /// not allow it.
+    Unanalysable {
+        name: String,
+    },
+}
+
+impl fmt::Display for ParameterError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Filter(error) => write!(f, "invalid filter: {}", error),
+            Self::KeySize { n, key_size } => write!(
+                f,
+                "the filter reads {} key bits out of a key of {} bits",
+                n, key_size
+            ),
+            Self::Fhe(reason) => write!(f, "invalid FHE parameters: {}", reason),
+            Self::Format(reason) => write!(f, "malformed parameter set: {}", reason),
+            Self::KeyLength { expected, found } => write!(
+                f,
+                "expected a symmetric key of {} bits, found {} bits",
+                expected, found
+            ),
+            Self::Insecure { name, bits } => write!(
+                f,
+                "{} only reaches {:.1} bits of security, and must be explicitly allowed",
+                name, bits
+            ),
+            Self::Unanalysable { name } => write!(
+                f,
+                "the security of {} cannot be estimated, and must be explicitly allowed",
+                name
+            ),
+        }
+    }
+}
+
+impl Error for ParameterError {}
+
+impl From<FilterError> for ParameterError {
+    fn from(error: FilterError) -> Self {
+        Self::Filter(error)
+    }
+}
+
+impl ParameterSet {
+    /// Parses and validates a set written in TOML. Sets estimated below 80 bits of security
+    /// are refused unless `allow_insecure` is set, see `ensure_secure`.
+    pub fn from_toml(text: &str, allow_insecure: bool) -> Result<Self, ParameterError> {
+        let set: Self = toml::from_str(text).map_err(|e| ParameterError::Format(e.to_string()))?;
+        set.validate()?;
+        set.ensure_secure(allow_insecure)?;
+        Ok(set)
+    }
+
+    /// Parses and validates a set written in JSON, with the same checks as `from_toml`.
+    pub fn from_json(text: &str, allow_insecure: bool) -> Result<Self, ParameterError> {
+        let set: Self =
+            serde_json::from_str(text).map_err(|e| ParameterError::Format(e.to_string()))?;
+        set.validate()?;
+        set.ensure_secure(allow_insecure)?;
+        Ok(set)
+    }
+
+    pub fn to_toml(&self) -> String {
+        toml::to_string(self).unwrap()
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap()
+    }
+
+    /// Loads a parameter set from a `.toml` or `.json` file, with the checks of `from_toml`.
+    pub fn load(path: impl AsRef<Path>, allow_insecure: bool) -> Result<Self, ParameterError> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path)
+            .map_err(|e| ParameterError::Format(format!("{}: {}", path.display(), e)))?;
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("toml") => Self::from_toml(&text, allow_insecure),
+            Some("json") => Self::from_json(&text, allow_insecure),
+            _ => Err(ParameterError::Format(format!(
+                "{}: expected a .toml or .json file",
+                path.display()
+            ))),
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), ParameterError> {
+        if self.n == 0 || self.n > self.key_size {
+            return Err(ParameterError::KeySize {
+                n: self.n,
+                key_size: self.key_size,
+            });
+        }
+        self.filter.validate(self.n)?;
+        let invalid = |reason: String| Err(ParameterError::Fhe(reason));
+        if self.glwe_dimension == 0 {
+            return invalid("the GLWE dimension must be positive".to_string());
+        }
+        if !self.polynomial_size.is_power_of_two() {
+            return invalid(format!(
+                "the polynomial size {} is not a power of two",
+                self.polynomial_size
+            ));
+        }
+        if self.base_log == 0
+            || self.level_count == 0
+            || self.base_log * self.level_count > Torus::BITS as usize
+        {
+            return invalid(format!(
+                "a decomposition of {} levels in base 2^{} does not fit in the torus",
+                self.level_count, self.base_log
+            ));
+        }
+        if !(self.std_dev > 0. && self.std_dev < 1.) {
+            return invalid(format!(
+                "the noise standard deviation {} is not in (0, 1)",
+                self.std_dev
+            ));
+        }
+        Ok(())
+    }
+
+    /// Refuses sets estimated below 80 bits of security, or whose filter is too large to be
+    /// analysed, unless `allow_insecure` is set.
+    pub fn ensure_secure(&self, allow_insecure: bool) -> Result<(), ParameterError> {
+        if allow_insecure {
+            return Ok(());
+        }
+        match self.security_estimate() {
+            Some(estimate) if estimate.bits() < 80. => Err(ParameterError::Insecure {
+                name: self.name.clone(),
+                bits: estimate.bits(),
+            }),
+            Some(_) => Ok(()),
+            None => Err(ParameterError::Unanalysable {
+                name: self.name.clone(),
+            }),
+        }
+    }
+
+    pub fn parameters(&self) -> Parameters {
+        Parameters {
+            n: self.n,
+            key_size: self.key_size,
+            filter: self.filter.clone(),
+            prng: self.prng,
+        }
+    }
+
+    /// Bit security of the set, or `None` when its filter is too large to be analysed.
+    pub fn security_estimate(&self) -> Option<SecurityEstimate> {
+        self.parameters().security_estimate()
+    }
+
+    /// Noise of the transciphered bits, and their failure probability.
+    pub fn noise_estimate(&self) -> NoiseEstimate {
+        NoiseModel::from_parameter_set(self).filter(&self.filter, self.n)
+    }
+
+    /// Operations issued by one keystream bit, whitening of the key included. Insecure sets are
+    /// counted as well, since nothing is encrypted.
+    pub fn operation_count(&self) -> OperationCount {
+        let mut encrypter =
+            Encrypter::from_parameter_set(CountingKeyBit::key(self.key_size), self, 0, true)
+                .unwrap_or_else(|error| panic!("{}: {}", self.name, error));
+        encrypter.key()[0].reset();
+        let mut bit = [CountingBit::default()];
+        encrypter.decrypt(&mut bit, &[false]);
+        bit[0].count()
+    }
+
+    /// Boolean circuit of the first keystream bit for `nonce`, as a function of the whole
+    /// symmetric key: the selection and whitening of the key bits are fixed by the nonce, and
+    /// the whitening negations are part of the circuit. See `SymbolicBit::to_bristol` and
+    /// `SymbolicBit::to_blif` to export it. Insecure sets are allowed, the circuit being only
+    /// analysed.
+    pub fn circuit(&self, nonce: Nonce) -> SymbolicBit {
+        let mut encrypter =
+            Encrypter::from_parameter_set(SymbolicKeyBit::key(self.key_size), self, nonce, true)
+                .unwrap_or_else(|error| panic!("{}: {}", self.name, error));
+        let mut bit = [SymbolicBit::zero(None, None)];
+        encrypter.decrypt(&mut bit, &[false]);
+        let [bit] = bit;
+        bit
+    }
+
+    /// Core-SVP cost, in bits, of the primal attack on the GLWE encryption of the key.
+    pub fn fhe_security(&self) -> f64 {
+        noise::primal_usvp(self.glwe_dimension * self.polynomial_size, self.std_dev)
+    }
+
+    /// Same instance with the cheapest FHE parameters reaching `security` bits against the
+    /// primal attack, and an estimated failure probability of the transciphered bits of at most
+    /// `2^log2_failure`. The FHE parameters of `self` are ignored.
+    ///
+    /// The failure probability is not a bound: it is computed from `noise_estimate`, the
+    /// largest noise over a sample of keys, with twice its variance as a heuristic margin for
+    /// the keys outside the sample. A key noisier than that margin fails more often.
+    pub fn tune(&self, security: f64, log2_failure: f64) -> Result<Self, ParameterError> {
+        noise::tune(self, security, log2_failure)
+    }
+
+    pub fn fhe_parameters(
+        &self,
+    ) -> (
+        GlweDimension,
+        PolynomialSize,
+        DecompositionBaseLog,
+        DecompositionLevelCount,
+        StandardDev,
+    ) {
+        (
+            GlweDimension(self.glwe_dimension),
+            PolynomialSize(self.polynomial_size),
+            DecompositionBaseLog(self.base_log),
+            DecompositionLevelCount(self.level_count),
+            StandardDev::from_standard_dev(self.std_dev),
+        )
+    }
+
+    pub fn generate_fhe_key(&self) -> GlweSecretKey<BinaryKeyKind, Vec<Torus>> {
+        let env_var = env::var("KEY_DIRECTORY").ok();
+        let path = env_var.as_ref().map(|s| &**s);
+        let key_stored = path
+            .map(|p| Path::new(format!("{}/keys/{}/fhe", p, self.name).as_str()).is_dir())
+            .unwrap_or(false);
+
+        if key_stored {
+            let sk_serialized = fs::read(format!(
+                "{}/keys/{}/fhe/secret_key",
+                path.unwrap(),
+                self.name
+            ))
+            .unwrap();
+            bincode::deserialize(&sk_serialized).unwrap()
+        } else {
+            let mut secret_generator = SecretRandomGenerator::new(None);
+            let (glwe_dimension, poly_size, _base_log, _level, _std_dev) = self.fhe_parameters();
+
+            let sk =
+                GlweSecretKey::generate_binary(glwe_dimension, poly_size, &mut secret_generator);
+            if path.is_some() {
+                fs::create_dir_all(format!("{}/keys/{}/fhe", path.unwrap(), self.name)).unwrap();
+                fs::write(
+                    format!("{}/keys/{}/fhe/secret_key", path.unwrap(), self.name),
+                    &bincode::serialize(&sk).unwrap(),
+                )
+                .unwrap();
+            }
+            sk
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        filter::{FilterExpression, FilterType},
+        SystemParameters, ToyParameters,
+    };
+    use strum::IntoEnumIterator;
+
+    #[test]
+    fn round_trip() {
+        for params in SystemParameters::iter() {
+            let set = params.parameter_set();
+            assert_eq!(
+                ParameterSet::from_toml(&set.to_toml(), false),
+                Ok(set.clone())
+            );
+            assert_eq!(ParameterSet::from_json(&set.to_json(), false), Ok(set));
+        }
+        let mut set = SystemParameters::n144.parameter_set();
+        set.filter = Filter::from_expression(
+            "linear 81\nthreshold 63 32"
+                .parse::<FilterExpression>()
+                .unwrap(),
+            144,
+        )
+        .unwrap();
+        assert_eq!(
+            ParameterSet::from_toml(&set.to_toml(), false),
+            Ok(set.clone())
+        );
+        assert_eq!(ParameterSet::from_json(&set.to_json(), false), Ok(set));
+
+        let toy = ToyParameters::dsm.parameter_set(true).unwrap();
+        assert!(matches!(
+            ParameterSet::from_toml(&toy.to_toml(), false),
+            Err(ParameterError::Insecure { .. })
+        ));
+        assert!(matches!(
+            ParameterSet::from_json(&toy.to_json(), false),
+            Err(ParameterError::Insecure { .. })
+        ));
+        assert_eq!(ParameterSet::from_toml(&toy.to_toml(), true), Ok(toy));
+    }
+
+    #[test]
+    fn documented_format() {
+        let text = "name = \"FiLIP 144\"\nn = 144\nkey_size = 16384\nprng = \"AesCtr\"\n\
+                    glwe_dimension = 3\npolynomial_size = 512\nbase_log = 15\nlevel_count = 1\n\
+                    std_dev = 3.53e-10\n\n[filter]\nparameters = [81, 32]\ncategory = \"XorThr\"\n";
+        let set = ParameterSet::from_toml(text, false).unwrap();
+        assert_eq!(set, SystemParameters::n144.parameter_set());
+        assert_eq!(
+            set.security_estimate(),
+            Some(SystemParameters::n144.security_estimate())
+        );
+    }
+
+    #[test]
+    fn validation() {
+        let set = SystemParameters::n144.parameter_set();
+        let check = |change: &dyn Fn(&mut ParameterSet)| {
+            let mut set = set.clone();
+            change(&mut set);
+            set.validate()
+        };
+        assert!(check(&|_| ()).is_ok());
+        assert_eq!(
+            check(&|s| s.key_size = 100),
+            Err(ParameterError::KeySize {
+                n: 144,
+                key_size: 100
+            })
+        );
+        assert!(matches!(
+            check(&|s| s.filter = Filter::new(FilterType::XorThr, &[81, 64])),
+            Err(ParameterError::Filter(FilterError::Invalid(_)))
+        ));
+        let mut n1216 = SystemParameters::n1216.parameter_set();
+        n1216.n = 1215;
+        assert_eq!(
+            n1216.validate(),
+            Err(ParameterError::Filter(FilterError::Arity {
+                expected: 1215,
+                found: 1216
+            }))
+        );
+        assert!(matches!(
+            check(&|s| s.polynomial_size = 1000),
+            Err(ParameterError::Fhe(_))
+        ));
+        assert!(matches!(
+            check(&|s| s.level_count = 13),
+            Err(ParameterError::Fhe(_))
+        ));
+        assert!(matches!(
+            check(&|s| s.std_dev = 0.),
+            Err(ParameterError::Fhe(_))
+        ));
+        assert!(matches!(
+            ParameterSet::from_toml("n = 144", true),
+            Err(ParameterError::Format(_))
+        ));
+    }
+
+    #[test]
+    fn tuning() {
+        let toy = ToyParameters::xor_thr.parameter_set(true).unwrap();
+        let tuned = toy.tune(128., -64.).unwrap();
+        assert!(tuned.validate().is_ok());
+        assert!(tuned.fhe_security() >= 128.);
+        let variance = noise::NOISE_MARGIN * tuned.noise_estimate().variance();
+        assert!(NoiseEstimate::new(variance).log2_failure_probability() <= -64.);
+        assert_eq!((tuned.n, &tuned.filter), (toy.n, &toy.filter));
+        // Lower security allows a smaller GLWE problem.
+        let cheaper = toy.tune(80., -64.).unwrap();
+        assert!(
+            cheaper.glwe_dimension * cheaper.polynomial_size
+                < tuned.glwe_dimension * tuned.polynomial_size
+        );
+        assert!(matches!(toy.tune(1e6, -64.), Err(ParameterError::Fhe(_))));
+    }
+}
@@ -1,21 +1,55 @@
-use std::{env, fs, path::Path};
-
 use crate::{
+    analysis::{FilterProperties, SecurityEstimate},
     filter::{Filter, FilterType},
+    noise::NoiseEstimate,
+    prng::PrngType,
     Torus,
 };
 use concrete_commons::{
-    dispersion::StandardDev,
+    dispersion::{DispersionParameter, StandardDev},
     key_kinds::BinaryKeyKind,
     parameters::{DecompositionBaseLog, DecompositionLevelCount, GlweDimension, PolynomialSize},
 };
-use concrete_core::crypto::secret::{generators::SecretRandomGenerator, GlweSecretKey};
+use concrete_core::crypto::secret::GlweSecretKey;
+use strum::IntoEnumIterator;
 use strum_macros::EnumIter;
 
+use super::{ParameterError, ParameterSet};
+
 pub struct Parameters {
     pub(crate) n: usize,
     pub(crate) key_size: usize,
     pub(crate) filter: Filter,
+    pub(crate) prng: PrngType,
+}
+
+impl Parameters {
+    /// Number of key bits fed to the filter at each keystream bit.
+    pub fn n(&self) -> usize {
+        self.n
+    }
+
+    pub fn key_size(&self) -> usize {
+        self.key_size
+    }
+
+    /// Generator of the subset selection and whitening.
+    pub fn prng(&self) -> PrngType {
+        self.prng
+    }
+
+    /// Cryptographic properties of the filter, over its `n` inputs, or `None` when the filter
+    /// is too large to be analysed, see `FilterProperties::new`.
+    pub fn filter_properties(&self) -> Option<FilterProperties> {
+        FilterProperties::new(&self.filter.expression(self.n))
+    }
+
+    /// Bit security against the attacks of the FiLIP analysis, or `None` when the filter is too
+    /// large to be analysed.
+    pub fn security_estimate(&self) -> Option<SecurityEstimate> {
+        self.filter_properties()
+            .map(|properties| SecurityEstimate::new(&properties, self.n, self.key_size))
+    }
 }
 
 #[allow(non_camel_case_types)]
@@ -33,6 +67,7 @@ impl SystemParameters {
                 key_size: 16384,
                 n: 1216,
                 filter: Filter::new(FilterType::DSM, &[128, 64, 0, 80, 0, 0, 0, 80]),
+                prng: PrngType::AesCtr,
             },
             Self::n1280 => Parameters {
                 key_size: 4096,
@@ -41,15 +76,23 @@ impl SystemParameters {
                     FilterType::DSM,
                     &[128, 64, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 64],
                 ),
+                prng: PrngType::AesCtr,
             },
             Self::n144 => Parameters {
                 key_size: 16384,
                 n: 144,
                 filter: Filter::new(FilterType::XorThr, &[81, 32]),
+                prng: PrngType::AesCtr,
             },
         }
     }
 
+    pub fn security_estimate(&self) -> SecurityEstimate {
+        self.parameters()
+            .security_estimate()
+            .expect("the filters of the presets can be analysed")
+    }
+
     pub fn name(&self) -> String {
         (match self {
             SystemParameters::n1216 => "FiLIP 1216",
@@ -59,6 +102,42 @@ impl SystemParameters {
         .to_string()
     }
 
+    pub fn noise_estimate(&self) -> NoiseEstimate {
+        self.parameter_set().noise_estimate()
+    }
+
+    /// Security level claimed for the instance by its source, in bits.
+    pub fn claimed_security(&self) -> usize {
+        match self {
+            Self::n1216 | Self::n1280 | Self::n144 => 128,
+        }
+    }
+
+    /// Publication introducing the instance.
+    pub fn source(&self) -> &'static str {
+        match self {
+            Self::n1216 | Self::n1280 => {
+                "Méaux, Carlet, Journault, Standaert. Improved filter permutators for efficient \
+                 FHE: better instances and implementations. INDOCRYPT 2019"
+            }
+            Self::n144 => {
+                "Hoffmann, Méaux, Ricosset. Transciphering, using FiLIP and TFHE for an efficient \
+                 delegation of computation. INDOCRYPT 2020"
+            }
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::iter().find(|params| params.name() == name)
+    }
+
+    /// FHE parameters of the transciphering, given by tuning each preset on its own filter with
+    /// `ParameterSet::tune(claimed_security, -128.)`. The noise estimates of the filters differ
+    /// with their depth, for predicted failure probabilities of about `2^-635` for FiLIP 1216,
+    /// `2^-537` for FiLIP 1280 and `2^-518` for FiLIP 144, but all three fit the cheapest
+    /// candidate reaching 128 bits, which they therefore share. These probabilities are not
+    /// bounds: they are estimated from the noisiest of a sample of keys, and the tuner only
+    /// adds a heuristic margin for the other keys, see `ParameterSet::tune`.
     pub fn fhe_parameters(
         &self,
     ) -> (
@@ -70,59 +149,142 @@ impl SystemParameters {
     ) {
         match self {
             SystemParameters::n1216 => (
-                GlweDimension(1),
-                PolynomialSize(1024),
-                DecompositionBaseLog(5),
-                DecompositionLevelCount(6),
-                StandardDev::from_standard_dev(10_f64.powf(-9.0)),
+                GlweDimension(3),
+                PolynomialSize(512),
+                DecompositionBaseLog(15),
+                DecompositionLevelCount(1),
+                StandardDev::from_standard_dev(3.53e-10),
             ),
             SystemParameters::n1280 => (
-                GlweDimension(1),
-                PolynomialSize(1024),
-                DecompositionBaseLog(5),
-                DecompositionLevelCount(6),
-                StandardDev::from_standard_dev(10_f64.powf(-9.0)),
+                GlweDimension(3),
+                PolynomialSize(512),
+                DecompositionBaseLog(15),
+                DecompositionLevelCount(1),
+                StandardDev::from_standard_dev(3.53e-10),
             ),
             SystemParameters::n144 => (
-                GlweDimension(1),
-                PolynomialSize(1024),
-                DecompositionBaseLog(5),
-                DecompositionLevelCount(6),
-                StandardDev::from_standard_dev(10_f64.powf(-9.0)),
+                GlweDimension(3),
+                PolynomialSize(512),
+                DecompositionBaseLog(15),
+                DecompositionLevelCount(1),
+                StandardDev::from_standard_dev(3.53e-10),
             ),
         }
     }
 
+    /// The preset as a parameter set, which can be saved to a file and edited.
+    pub fn parameter_set(&self) -> ParameterSet {
+        let Parameters {
+            n,
+            key_size,
+            filter,
+            prng,
+        } = self.parameters();
+        let (glwe_dimension, polynomial_size, base_log, level_count, std_dev) =
+            self.fhe_parameters();
+        ParameterSet {
+            name: self.name(),
+            n,
+            key_size,
+            prng,
+            glwe_dimension: glwe_dimension.0,
+            polynomial_size: polynomial_size.0,
+            base_log: base_log.0,
+            level_count: level_count.0,
+            std_dev: std_dev.get_standard_dev(),
+            filter,
+        }
+    }
+
     pub fn generate_fhe_key(&self) -> GlweSecretKey<BinaryKeyKind, Vec<Torus>> {
-        let env_var = env::var("KEY_DIRECTORY").ok();
-        let path = env_var.as_ref().map(|s| &**s);
-        let key_stored = path
-            .map(|p| Path::new(format!("{}/keys/{}/fhe", p, self.name()).as_str()).is_dir())
-            .unwrap_or(false);
-
-        if key_stored {
-            let sk_serialized = fs::read(format!(
-                "{}/keys/{}/fhe/secret_key",
-                path.unwrap(),
-                self.name()
-            ))
-            .unwrap();
-            bincode::deserialize(&sk_serialized).unwrap()
-        } else {
-            let mut secret_generator = SecretRandomGenerator::new(None);
-            let (glwe_dimension, poly_size, _base_log, _level, _std_dev) = self.fhe_parameters();
-
-            let sk =
-                GlweSecretKey::generate_binary(glwe_dimension, poly_size, &mut secret_generator);
-            if path.is_some() {
-                fs::create_dir_all(format!("{}/keys/{}/fhe", path.unwrap(), self.name())).unwrap();
-                fs::write(
-                    format!("{}/keys/{}/fhe/secret_key", path.unwrap(), self.name()),
-                    &bincode::serialize(&sk).unwrap(),
-                )
-                .unwrap();
-            }
-            sk
+        self.parameter_set().generate_fhe_key()
+    }
+}
+
+/// Insecure instances, small enough to run the whole homomorphic pipeline in seconds. They
+/// only serve to test the pipeline, and are kept out of `SystemParameters`.
+#[allow(non_camel_case_types)]
+#[derive(Debug, EnumIter)]
+pub enum ToyParameters {
+    dsm,
+    xor_thr,
+}
+
+impl ToyParameters {
+    /// The toy parameter set, which is only given out when `allow_insecure` is set.
+    pub fn parameter_set(&self, allow_insecure: bool) -> Result<ParameterSet, ParameterError> {
+        let (name, filter) = match self {
+            Self::dsm => ("Toy DSM", Filter::new(FilterType::DSM, &[2, 0, 2])),
+            Self::xor_thr => ("Toy XOR-THR", Filter::new(FilterType::XorThr, &[3, 3])),
+        };
+        let set = ParameterSet {
+            name: name.to_string(),
+            n: 8,
+            key_size: 64,
+            prng: PrngType::AesCtr,
+            glwe_dimension: 1,
+            polynomial_size: 256,
+            base_log: 5,
+            level_count: 6,
+            std_dev: 10_f64.powf(-9.0),
+            filter,
+        };
+        set.validate()?;
+        set.ensure_secure(allow_insecure)?;
+        Ok(set)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn catalog() {
+        for params in SystemParameters::iter() {
+            let name = params.name();
+            assert_eq!(
+                SystemParameters::from_name(&name).map(|p| p.name()),
+                Some(name)
+            );
+            assert_eq!(params.parameter_set().validate(), Ok(()));
+            assert!([80, 128].contains(&params.claimed_security()));
+            assert!(
+                params.parameter_set().fhe_security() >= params.claimed_security() as f64,
+                "{}",
+                params.name()
+            );
+            assert!(!params.source().is_empty());
+        }
+    }
+
+    #[test]
+    fn tuned() {
+        for params in SystemParameters::iter() {
+            let set = params.parameter_set();
+            let tuned = set.tune(params.claimed_security() as f64, -128.).unwrap();
+            // The standard deviation is rounded up to three digits.
+            let ((k, n, b, l, std_dev), expected) = (set.fhe_parameters(), tuned.fhe_parameters());
+            assert_eq!(
+                (k, n, b, l),
+                (expected.0, expected.1, expected.2, expected.3)
+            );
+            let ratio = std_dev.get_standard_dev() / expected.4.get_standard_dev();
+            assert!((1. ..1.001).contains(&ratio), "{}", set.name);
+            assert!(set.noise_estimate().log2_failure_probability() < -500.);
+        }
+    }
+
+    #[test]
+    fn toys() {
+        for toy in ToyParameters::iter() {
+            assert!(matches!(
+                toy.parameter_set(false),
+                Err(ParameterError::Insecure { .. })
+            ));
+            let set = toy.parameter_set(true).unwrap();
+            assert!(set.security_estimate().unwrap().bits() < 80.);
+            assert!(SystemParameters::from_name(&set.name).is_none());
         }
     }
 }
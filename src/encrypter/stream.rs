@@ -0,0 +1,66 @@
+use std::io::{Read, Result, Write};
+
+use crate::Encrypter;
+
+/// Encrypts everything written through it with the keystream of a clear encrypter before
+/// passing it to the inner writer.
+pub struct KeystreamWriter<W: Write> {
+    encrypter: Encrypter<bool>,
+    inner: W,
+}
+
+impl<W: Write> KeystreamWriter<W> {
+    pub fn new(encrypter: Encrypter<bool>, inner: W) -> Self {
+        Self { encrypter, inner }
+    }
+
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    pub fn into_inner(self) -> (Encrypter<bool>, W) {
+        (self.encrypter, self.inner)
+    }
+}
+
+impl<W: Write> Write for KeystreamWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        // The keystream is consumed as soon as the bytes are encrypted, so all of them have to
+        // reach the inner writer.
+        let ciphertext = self.encrypter.encrypt_bytes(buf);
+        self.inner.write_all(&ciphertext)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Decrypts everything read from the inner reader with the keystream of a clear encrypter.
+pub struct KeystreamReader<R: Read> {
+    encrypter: Encrypter<bool>,
+    inner: R,
+}
+
+impl<R: Read> KeystreamReader<R> {
+    pub fn new(encrypter: Encrypter<bool>, inner: R) -> Self {
+        Self { encrypter, inner }
+    }
+
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    pub fn into_inner(self) -> (Encrypter<bool>, R) {
+        (self.encrypter, self.inner)
+    }
+}
+
+impl<R: Read> Read for KeystreamReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.encrypter.apply_keystream(&mut buf[..read]);
+        Ok(read)
+    }
+}
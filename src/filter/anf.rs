@@ -0,0 +1,40 @@
+use crate::multiplexer::Multiplexer;
+
+/// Xor of the monomials, each monomial being the list of its variables. The empty monomial is
+/// the constant one.
+pub(super) fn anf<M: Multiplexer>(x: &[M], monomials: &[Vec<usize>]) -> M::Bit {
+    let mut returnValue = x[0].constant(false);
+    for monomial in monomials {
+        returnValue ^= match monomial.split_first() {
+            Some((&first, rest)) => {
+                let mut temp = x[first].as_bit();
+                for &j in rest {
+                    temp &= x[j].clone();
+                }
+                temp
+            }
+            None => x[0].constant(true),
+        };
+    }
+    returnValue
+}
+
+/// Evaluates the truth table `table`, whose entry `i` is the value of the function on the
+/// inputs `x_j = (i >> j) & 1`, with a tree of CMUX selecting on `x_0` first.
+pub(super) fn table<M: Multiplexer>(x: &[M], table: &[bool]) -> M::Bit {
+    let mut level = table
+        .chunks(2)
+        .map(|pair| match (pair[0], pair[1]) {
+            (false, true) => x[0].as_bit(),
+            (true, false) => !x[0].as_bit(),
+            (value, _) => x[0].constant(value),
+        })
+        .collect::<Vec<_>>();
+    for x_j in x[1..].iter() {
+        level = level
+            .chunks(2)
+            .map(|pair| x_j.mux(&pair[1], &pair[0]))
+            .collect();
+    }
+    level.pop().unwrap()
+}
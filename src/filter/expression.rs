@@ -0,0 +1,178 @@
+use std::{error::Error, fmt};
+
+use super::{
+    anf::{anf, table},
+    symmetric::symmetric,
+    xor_maj::majority,
+    xor_thr::threshold,
+};
+use crate::multiplexer::Multiplexer;
+use serde::{Deserialize, Serialize};
+
+/// Filter built as a direct sum of sub-filters: each node reads its own block of consecutive
+/// inputs, in the order the nodes appear.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FilterExpression {
+    /// Xor of the sub-filters, over disjoint consecutive blocks of inputs.
+    Xor(Vec<Self>),
+    /// Xor of `k` inputs.
+    Linear(usize),
+    /// Product of `d` inputs.
+    Monomial(usize),
+    /// Whether at least `d` of `m` inputs are set.
+    Threshold { m: usize, d: usize },
+    /// Majority of an odd number `m` of inputs.
+    Majority(usize),
+    /// Symmetric function of `len - 1` inputs, entry `w` being its value on the inputs of
+    /// Hamming weight `w`.
+    Symmetric(Vec<bool>),
+    /// Algebraic normal form over `arity` inputs: xor of the monomials, each one given by the
+    /// indices of its inputs within the block. The empty monomial is the constant one. Every input
+    /// must appear in some monomial, except in `FilterExpression::wpb`, whose last input never
+    /// does.
+    Anf {
+        arity: usize,
+        monomials: Vec<Vec<usize>>,
+    },
+    /// Truth table over `log2(len)` inputs, entry `i` being the value on the inputs
+    /// `x_j = (i >> j) & 1`.
+    Table(Vec<bool>),
+}
+
+impl FilterExpression {
+    /// Number of inputs read by the expression.
+    pub fn arity(&self) -> usize {
+        match self {
+            Self::Xor(terms) => terms.iter().map(Self::arity).sum(),
+            Self::Linear(k) => *k,
+            Self::Monomial(d) => *d,
+            Self::Threshold { m, .. } | Self::Majority(m) => *m,
+            Self::Symmetric(table) => table.len() - 1,
+            Self::Anf { arity, .. } => *arity,
+            Self::Table(table) => table.len().trailing_zeros() as usize,
+        }
+    }
+
+    /// Checks that every node is well formed and that the expression reads exactly `n` inputs.
+    pub fn validate(&self, n: usize) -> Result<(), FilterError> {
+        self.validate_nodes()?;
+        if self.arity() == n {
+            Ok(())
+        } else {
+            Err(FilterError::Arity {
+                expected: n,
+                found: self.arity(),
+            })
+        }
+    }
+
+    pub(super) fn validate_nodes(&self) -> Result<(), FilterError> {
+        let invalid = |reason: &str| Err(FilterError::Invalid(format!("{:?}: {}", self, reason)));
+        match *self {
+            Self::Xor(ref terms) if terms.is_empty() => invalid("empty xor"),
+            Self::Xor(ref terms) => terms.iter().try_for_each(Self::validate_nodes),
+            Self::Linear(0) | Self::Monomial(0) => invalid("no input"),
+            Self::Threshold { m, d } if d == 0 || d > m => {
+                invalid("the threshold must lie between one and the number of inputs")
+            }
+            Self::Majority(m) if m % 2 == 0 => invalid("even number of inputs"),
+            Self::Anf {
+                arity,
+                ref monomials,
+            } => {
+                let mut used = vec![false; arity];
+                let mut sorted = Vec::with_capacity(monomials.len());
+                for monomial in monomials {
+                    let mut monomial = monomial.clone();
+                    monomial.sort_unstable();
+                    if monomial.iter().any(|&j| j >= arity) {
+                        return invalid("variable out of range");
+                    }
+                    if monomial.windows(2).any(|w| w[0] == w[1]) {
+                        return invalid("variable repeated in a monomial");
+                    }
+                    for &j in &monomial {
+                        used[j] = true;
+                    }
+                    sorted.push(monomial);
+                }
+                sorted.sort();
+                if sorted.windows(2).any(|w| w[0] == w[1]) {
+                    invalid("repeated monomial")
+                } else if arity == 0 {
+                    invalid("no input")
+                } else if used.contains(&false) && !Self::is_wpb(arity, &sorted) {
+                    invalid("unused variable")
+                } else {
+                    Ok(())
+                }
+            }
+            Self::Symmetric(ref table) if table.windows(2).all(|w| w[0] == w[1]) => {
+                invalid("constant function")
+            }
+            Self::Table(ref table) if table.len() < 2 || !table.len().is_power_of_two() => {
+                invalid("the table length must be a power of two, at least two")
+            }
+            _ => Ok(()),
+        }
+    }
+
+    pub(crate) fn call<M: Multiplexer>(&self, x: &[M]) -> M::Bit {
+        match self {
+            Self::Xor(terms) => {
+                let mut j = terms[0].arity();
+                let mut returnValue = terms[0].call(&x[..j]);
+                for term in terms[1..].iter() {
+                    returnValue ^= term.call(&x[j..j + term.arity()]);
+                    j += term.arity();
+                }
+                returnValue
+            }
+            Self::Linear(_) => {
+                let mut returnValue = x[0].as_bit();
+                for x_j in x[1..].iter() {
+                    returnValue ^= x_j.as_bit();
+                }
+                returnValue
+            }
+            Self::Monomial(_) => {
+                let mut returnValue = x[0].as_bit();
+                for x_j in x[1..].iter() {
+                    returnValue &= x_j.clone();
+                }
+                returnValue
+            }
+            Self::Threshold { d, .. } => threshold(x, *d),
+            Self::Majority(_) => majority(x),
+            Self::Symmetric(t) => symmetric(x, t),
+            Self::Anf { monomials, .. } => anf(x, monomials),
+            Self::Table(t) => table(x, t),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FilterError {
+    /// The filter does not read as many inputs as the parameter set selects key bits.
+    Arity { expected: usize, found: usize },
+    /// A node of the filter expression is ill-formed.
+    Invalid(String),
+    /// The textual description of the filter could not be parsed.
+    Parse { line: usize, reason: String },
+}
+
+impl fmt::Display for FilterError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Arity { expected, found } => write!(
+                f,
+                "the filter reads {} inputs instead of {}",
+                found, expected
+            ),
+            Self::Invalid(reason) => write!(f, "invalid filter node {}", reason),
+            Self::Parse { line, reason } => write!(f, "line {}: {}", line, reason),
+        }
+    }
+}
+
+impl Error for FilterError {}
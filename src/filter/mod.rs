@@ -1,20 +1,34 @@
+mod anf;
 mod dsm;
+mod expression;
+mod parser;
+mod symmetric;
+mod wpb;
+mod xor_maj;
 mod xor_thr;
 
 use crate::multiplexer::Multiplexer;
 use dsm::*;
+pub use expression::{FilterError, FilterExpression};
+use serde::{Deserialize, Serialize};
+use xor_maj::*;
 use xor_thr::*;
 
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub enum FilterType {
     DSM,
     XorThr,
+    /// Xor of `k` inputs and of the majority of `m` other inputs, with parameters `[k, m]`.
+    XorMaj,
+    /// Direct sum of arbitrary sub-filters, see `FilterExpression`.
+    Expression(FilterExpression),
 }
 
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Filter {
-    category: FilterType,
+    #[serde(default)]
     parameters: Vec<usize>,
+    category: FilterType,
 }
 
 impl Filter {
@@ -25,12 +39,123 @@ impl Filter {
         }
     }
 
+    /// Filter evaluating `expression`, which must read exactly `n` inputs.
+    pub fn from_expression(expression: FilterExpression, n: usize) -> Result<Self, FilterError> {
+        expression.validate(n)?;
+        Ok(Self::new(FilterType::Expression(expression), &[]))
+    }
+
+    /// Filter described by `text`, in the format of `FilterExpression::from_str`, which must
+    /// read exactly `n` inputs.
+    pub fn parse(text: &str, n: usize) -> Result<Self, FilterError> {
+        Self::from_expression(text.parse()?, n)
+    }
+
+    /// Checks that the parameters are well formed and that the filter reads exactly `n` inputs.
+    pub fn validate(&self, n: usize) -> Result<(), FilterError> {
+        let p = &self.parameters;
+        let invalid = |reason: &str| Err(FilterError::Invalid(format!("{:?}: {}", self, reason)));
+        match &self.category {
+            FilterType::DSM if p.first().map_or(true, |&m_1| m_1 == 0) => {
+                invalid("DSM filters start with a non-empty linear part")
+            }
+            FilterType::XorThr | FilterType::XorMaj if p.len() != 2 || p[0] == 0 => {
+                invalid("expected parameters [k, d] with k > 0")
+            }
+            FilterType::XorThr if p[0] >= n || p[1] == 0 || p[1] > n - p[0] => {
+                invalid("the threshold must lie between one and the number of inputs")
+            }
+            FilterType::XorMaj if p[1] % 2 == 0 => invalid("even number of inputs"),
+            FilterType::Expression(expression) => expression.validate(n),
+            _ => match self.arity() {
+                Some(arity) if arity != n => Err(FilterError::Arity {
+                    expected: n,
+                    found: arity,
+                }),
+                _ => Ok(()),
+            },
+        }
+    }
+
+    /// The filter as a direct sum of sub-filters, when fed `n` inputs.
+    pub fn expression(&self, n: usize) -> FilterExpression {
+        let p = &self.parameters;
+        let terms = match &self.category {
+            FilterType::DSM => p
+                .iter()
+                .enumerate()
+                .filter(|&(_, &m_i)| m_i > 0)
+                .flat_map(|(i, &m_i)| match i {
+                    0 => vec![FilterExpression::Linear(m_i)],
+                    _ => vec![FilterExpression::Monomial(i + 1); m_i],
+                })
+                .collect(),
+            FilterType::XorThr => vec![
+                FilterExpression::Linear(p[0]),
+                FilterExpression::Threshold {
+                    m: n - p[0],
+                    d: p[1],
+                },
+            ],
+            FilterType::XorMaj => vec![
+                FilterExpression::Linear(p[0]),
+                FilterExpression::Majority(p[1]),
+            ],
+            FilterType::Expression(expression) => return expression.clone(),
+        };
+        FilterExpression::Xor(terms)
+    }
+
+    /// Number of inputs read by the filter, if it does not adapt to the input length.
+    pub fn arity(&self) -> Option<usize> {
+        match &self.category {
+            FilterType::DSM => Some(
+                self.parameters
+                    .iter()
+                    .enumerate()
+                    .map(|(i, m_i)| (i + 1) * m_i)
+                    .sum(),
+            ),
+            FilterType::XorThr => None,
+            FilterType::XorMaj => Some(self.parameters[0] + self.parameters[1]),
+            FilterType::Expression(expression) => Some(expression.arity()),
+        }
+    }
+
     pub fn call<M: Multiplexer>(&self, x: &[M]) -> M::Bit {
-        match self.category {
+        match &self.category {
             FilterType::DSM => dsm(x, &self.parameters),
             FilterType::XorThr => xor_thr(x, self.parameters[0], self.parameters[1]),
+            FilterType::XorMaj => xor_maj(x, self.parameters[0], self.parameters[1]),
+            FilterType::Expression(expression) => {
+                assert_eq!(
+                    x.len(),
+                    expression.arity(),
+                    "the filter expression reads {} inputs",
+                    expression.arity()
+                );
+                expression.call(x)
+            }
         }
     }
+
+    /// Outputs of the sub-filters of `expression(x.len())`, each one with its block of inputs:
+    /// their xor is `call(x)`.
+    pub fn stages<M: Multiplexer>(&self, x: &[M]) -> Vec<(FilterExpression, M::Bit)> {
+        let terms = match self.expression(x.len()) {
+            FilterExpression::Xor(terms) => terms,
+            term => vec![term],
+        };
+        let mut j = 0;
+        terms
+            .into_iter()
+            .map(|term| {
+                let output = term.call(&x[j..j + term.arity()]);
+                j += term.arity();
+                (term, output)
+            })
+            .collect()
+    }
 }
 
 #[cfg(test)]
@@ -44,6 +169,315 @@ mod tests {
         assert_eq!(result, true);
     }
 
+    #[test]
+    fn xor_thr_thresholds() {
+        // Every threshold, not only the majority of the presets, against the Hamming weight.
+        let k = 1;
+        for m in 1..=8 {
+            for d in 1..=m {
+                let f = Filter::new(FilterType::XorThr, &[k, d]);
+                for x in 0..1_usize << (k + m) {
+                    let input = (0..k + m).map(|i| (x >> i) & 1 == 1).collect::<Vec<_>>();
+                    let weight = input[k..].iter().filter(|&&b| b).count();
+                    assert_eq!(
+                        f.call(&input),
+                        input[0] ^ (weight >= d),
+                        "m = {}, d = {}",
+                        m,
+                        d
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn xor_maj() {
+        let (k, m) = (2, 5);
+        let f = Filter::new(FilterType::XorMaj, &[k, m]);
+        for x in 0..1_usize << (k + m) {
+            let input = (0..k + m).map(|i| (x >> i) & 1 == 1).collect::<Vec<_>>();
+            let linear = input[..k].iter().fold(false, |acc, &b| acc ^ b);
+            let weight = input[k..].iter().filter(|&&b| b).count();
+            assert_eq!(f.call(&input), linear ^ (weight > m / 2));
+        }
+    }
+
+    #[test]
+    fn threshold() {
+        for m in 1..9 {
+            for d in 1..=m {
+                let f = Filter::from_expression(FilterExpression::Threshold { m, d }, m).unwrap();
+                for x in 0..1_usize << m {
+                    let input = (0..m).map(|i| (x >> i) & 1 == 1).collect::<Vec<_>>();
+                    assert_eq!(f.call(&input), x.count_ones() as usize >= d);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn expression() {
+        use FilterExpression::*;
+        let expression = Xor(vec![
+            Linear(2),
+            Monomial(2),
+            Xor(vec![Threshold { m: 4, d: 3 }, Majority(3)]),
+        ]);
+        let f = Filter::from_expression(expression, 11).unwrap();
+        assert_eq!(f.arity(), Some(11));
+        for x in 0..1_usize << 11 {
+            let input = (0..11).map(|i| (x >> i) & 1 == 1).collect::<Vec<_>>();
+            let weight = |bits: &[bool]| bits.iter().filter(|&&b| b).count();
+            let expected = (input[0] ^ input[1])
+                ^ (input[2] & input[3])
+                ^ (weight(&input[4..8]) >= 3)
+                ^ (weight(&input[8..]) >= 2);
+            assert_eq!(f.call(&input), expected);
+        }
+    }
+
+    #[test]
+    fn expression_matches_dsm() {
+        let dsm = Filter::new(FilterType::DSM, &[2, 0, 1]);
+        let expression = FilterExpression::Xor(vec![
+            FilterExpression::Linear(2),
+            FilterExpression::Monomial(3),
+        ]);
+        let f = Filter::from_expression(expression, 5).unwrap();
+        for x in 0..1_usize << 5 {
+            let input = (0..5).map(|i| (x >> i) & 1 == 1).collect::<Vec<_>>();
+            assert_eq!(f.call(&input), dsm.call(&input));
+        }
+    }
+
+    #[test]
+    fn expression_validation() {
+        let expression = FilterExpression::Xor(vec![
+            FilterExpression::Linear(3),
+            FilterExpression::Majority(5),
+        ]);
+        assert_eq!(
+            Filter::from_expression(expression.clone(), 9).err(),
+            Some(FilterError::Arity {
+                expected: 9,
+                found: 8
+            })
+        );
+        assert!(Filter::from_expression(expression, 8).is_ok());
+        assert!(Filter::from_expression(FilterExpression::Majority(4), 4).is_err());
+        assert!(Filter::from_expression(FilterExpression::Xor(vec![]), 0).is_err());
+        assert!(Filter::from_expression(FilterExpression::Threshold { m: 3, d: 4 }, 3).is_err());
+    }
+
+    #[test]
+    fn symmetric() {
+        for m in 1..6 {
+            for values in 1..(1_usize << (m + 1)) - 1 {
+                let table = (0..=m).map(|w| (values >> w) & 1 == 1).collect::<Vec<_>>();
+                let f =
+                    Filter::from_expression(FilterExpression::Symmetric(table.clone()), m).unwrap();
+                for x in 0..1_usize << m {
+                    let input = (0..m).map(|i| (x >> i) & 1 == 1).collect::<Vec<_>>();
+                    assert_eq!(f.call(&input), table[x.count_ones() as usize]);
+                }
+            }
+        }
+        assert!(Filter::from_expression(FilterExpression::Symmetric(vec![true; 4]), 3).is_err());
+    }
+
+    #[test]
+    fn wpb() {
+        for n in [2, 4, 8, 16] {
+            let f = Filter::from_expression(FilterExpression::wpb(n), n).unwrap();
+            let mut ones = vec![0; n + 1];
+            for x in 0..1_usize << n {
+                let input = (0..n).map(|i| (x >> i) & 1 == 1).collect::<Vec<_>>();
+                ones[x.count_ones() as usize] += usize::from(f.call(&input));
+            }
+            assert_eq!(ones[0], 0);
+            assert_eq!(ones[n], 1);
+            for w in 1..n {
+                let binomial = (0..w).fold(1, |c, i| c * (n - i) / (i + 1));
+                assert_eq!(2 * ones[w], binomial);
+            }
+        }
+    }
+
+    #[test]
+    fn anf() {
+        let expression = FilterExpression::Anf {
+            arity: 3,
+            monomials: vec![vec![0, 1], vec![2], vec![]],
+        };
+        let f = Filter::from_expression(expression, 3).unwrap();
+        for x in 0..8 {
+            let input = (0..3).map(|i| (x >> i) & 1 == 1).collect::<Vec<_>>();
+            assert_eq!(f.call(&input), !((input[0] & input[1]) ^ input[2]));
+        }
+        let unused = FilterExpression::Anf {
+            arity: 4,
+            monomials: vec![vec![0, 1], vec![2]],
+        };
+        assert!(Filter::from_expression(unused, 4).is_err());
+        // Same monomials as the WPB function of 4 inputs but one, which leaves x2 unused.
+        let truncated = FilterExpression::Anf {
+            arity: 4,
+            monomials: vec![vec![0], vec![0, 1]],
+        };
+        assert!(Filter::from_expression(truncated, 4).is_err());
+        // WPB function of 8 inputs with its inputs relabelled, which leaves x0 unused.
+        let relabelled = FilterExpression::Anf {
+            arity: 8,
+            monomials: vec![
+                vec![3, 5, 6, 7],
+                vec![4, 1],
+                vec![7, 6],
+                vec![7],
+                vec![4],
+                vec![5],
+                vec![2],
+            ],
+        };
+        let f = Filter::from_expression(relabelled, 8).unwrap();
+        let wpb = Filter::from_expression(FilterExpression::wpb(8), 8).unwrap();
+        let relabel = [7, 6, 5, 3, 4, 1, 2, 0];
+        for x in 0..1_usize << 8 {
+            let input = (0..8).map(|i| (x >> i) & 1 == 1).collect::<Vec<_>>();
+            let relabelled = (0..8).map(|i| input[relabel[i]]).collect::<Vec<_>>();
+            assert_eq!(f.call(&input), wpb.call(&relabelled));
+        }
+        // Two monomials of degree four cannot both split the inputs in halves.
+        let split = FilterExpression::Anf {
+            arity: 8,
+            monomials: vec![
+                vec![0, 1, 2, 3],
+                vec![0, 1, 4, 5],
+                vec![0, 1],
+                vec![4, 5],
+                vec![0],
+                vec![2],
+                vec![4],
+            ],
+        };
+        assert!(Filter::from_expression(split, 8).is_err());
+    }
+
+    #[test]
+    fn table() {
+        let values = (0..16)
+            .map(|i| (0x6ac5_u16 >> i) & 1 == 1)
+            .collect::<Vec<_>>();
+        let f = Filter::from_expression(FilterExpression::Table(values.clone()), 4).unwrap();
+        for (x, &value) in values.iter().enumerate() {
+            let input = (0..4).map(|i| (x >> i) & 1 == 1).collect::<Vec<_>>();
+            assert_eq!(f.call(&input), value);
+        }
+    }
+
+    #[test]
+    fn parse() {
+        use FilterExpression::*;
+        let text = "
+            # comment
+            linear 2
+            anf x2*x3 + x4 + 1 # local variables 0 to 2
+            table 0001
+            threshold 4 3
+        ";
+        let f = Filter::parse(text, 11).unwrap();
+        let expected = Filter::from_expression(
+            Xor(vec![
+                Linear(2),
+                Anf {
+                    arity: 3,
+                    monomials: vec![vec![0, 1], vec![2], vec![]],
+                },
+                Monomial(2),
+                Threshold { m: 4, d: 3 },
+            ]),
+            11,
+        )
+        .unwrap();
+        for x in 0..1_usize << 11 {
+            let input = (0..11).map(|i| (x >> i) & 1 == 1).collect::<Vec<_>>();
+            assert_eq!(f.call(&input), expected.call(&input));
+        }
+
+        let line = |text: &str| match text.parse::<FilterExpression>() {
+            Err(FilterError::Parse { line, .. }) => line,
+            _ => 0,
+        };
+        assert_eq!(line("linear 2\nanf x1*x2 + x3"), 2);
+        assert_eq!(line("linear 2\nanf x2*x3 + x5"), 2);
+        assert_eq!(line("table 010"), 1);
+        assert_eq!(line("majority"), 1);
+        assert_eq!(line("linear 1\ncubic 3"), 2);
+        assert_eq!(line("wpb 6"), 1);
+        assert_eq!(line("symmetric 111"), 1);
+        assert_eq!(
+            "wpb 4\nsymmetric 001".parse(),
+            Ok(Xor(vec![
+                FilterExpression::wpb(4),
+                Symmetric(vec![false, false, true])
+            ]))
+        );
+        assert!(Filter::parse("linear 2\nmajority 3", 6).is_err());
+    }
+
+    #[test]
+    fn stages() {
+        let filters = [
+            (Filter::new(FilterType::DSM, &[2, 0, 1]), 5),
+            (Filter::new(FilterType::XorThr, &[2, 4]), 9),
+            (Filter::new(FilterType::XorMaj, &[2, 5]), 7),
+        ];
+        for (f, n) in filters.iter() {
+            for x in 0..1_usize << n {
+                let input = (0..*n).map(|i| (x >> i) & 1 == 1).collect::<Vec<_>>();
+                let stages = f.stages(&input);
+                assert_eq!(
+                    stages.iter().fold(false, |acc, (_, bit)| acc ^ bit),
+                    f.call(&input)
+                );
+            }
+        }
+        let terms = filters[0]
+            .0
+            .stages(&[false; 5])
+            .into_iter()
+            .map(|(term, _)| term)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            terms,
+            vec![FilterExpression::Linear(2), FilterExpression::Monomial(3)]
+        );
+    }
+
+    #[test]
+    fn validation() {
+        assert!(Filter::new(FilterType::DSM, &[2, 0, 1]).validate(5).is_ok());
+        assert_eq!(
+            Filter::new(FilterType::DSM, &[2, 0, 1]).validate(6),
+            Err(FilterError::Arity {
+                expected: 6,
+                found: 5
+            })
+        );
+        assert!(Filter::new(FilterType::XorThr, &[81, 32])
+            .validate(144)
+            .is_ok());
+        assert!(Filter::new(FilterType::XorThr, &[81, 64])
+            .validate(144)
+            .is_err());
+        assert!(Filter::new(FilterType::XorThr, &[81])
+            .validate(144)
+            .is_err());
+        assert!(Filter::new(FilterType::XorMaj, &[81, 62])
+            .validate(143)
+            .is_err());
+    }
+
     #[test]
     fn dsm() {
         let f = Filter::new(FilterType::DSM, &[2, 0, 1]);
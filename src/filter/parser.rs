@@ -0,0 +1,132 @@
+//! Textual description of a filter expression, one sub-filter per line, each line reading the
+//! next block of inputs:
+//!
+//! ```text
+//! # Direct sum over 28 inputs
+//! linear 3
+//! monomial 2
+//! threshold 4 3
+//! majority 3
+//! anf x12*x13 + x12*x14 + x14 + 1
+//! table 0110
+//! symmetric 0110
+//! wpb 8
+//! ```
+//!
+//! `anf` takes a sum of monomials over the global input indices, which must cover exactly the
+//! next block, `table` the values of the function with `x_0` as the least significant bit of the
+//! entry index, `symmetric` the values of the function on each Hamming weight from zero to the
+//! number of inputs, and `wpb n` stands for `FilterExpression::wpb(n)`. Everything after a `#`
+//! is a comment.
+
+use std::{convert::TryInto, str::FromStr};
+
+use super::expression::{FilterError, FilterExpression};
+
+impl FromStr for FilterExpression {
+    type Err = FilterError;
+
+    fn from_str(s: &str) -> Result<Self, FilterError> {
+        let mut terms = Vec::new();
+        let mut offset = 0;
+        for (i, line) in s.lines().enumerate() {
+            let line = line.split('#').next().unwrap().trim();
+            if line.is_empty() {
+                continue;
+            }
+            let error = |reason: String| FilterError::Parse {
+                line: i + 1,
+                reason,
+            };
+            let (keyword, rest) = line
+                .split_once(char::is_whitespace)
+                .map_or((line, ""), |(keyword, rest)| (keyword, rest.trim()));
+            let term = match keyword {
+                "linear" => integers::<1>(rest).map(|[k]| FilterExpression::Linear(k)),
+                "monomial" => integers::<1>(rest).map(|[d]| FilterExpression::Monomial(d)),
+                "threshold" => {
+                    integers::<2>(rest).map(|[m, d]| FilterExpression::Threshold { m, d })
+                }
+                "majority" => integers::<1>(rest).map(|[m]| FilterExpression::Majority(m)),
+                "anf" => anf(rest, offset),
+                "table" => bits(rest).map(FilterExpression::Table),
+                "symmetric" => bits(rest).map(FilterExpression::Symmetric),
+                "wpb" => integers::<1>(rest).and_then(|[n]| {
+                    if n >= 2 && n.is_power_of_two() {
+                        Ok(FilterExpression::wpb(n))
+                    } else {
+                        Err("WPB functions are defined for powers of two".to_string())
+                    }
+                }),
+                _ => Err(format!("unknown sub-filter `{}`", keyword)),
+            }
+            .map_err(error)?;
+            term.validate_nodes().map_err(|e| error(e.to_string()))?;
+            offset += term.arity();
+            terms.push(term);
+        }
+        match terms.len() {
+            0 => Err(FilterError::Parse {
+                line: 0,
+                reason: "empty filter".to_string(),
+            }),
+            1 => Ok(terms.pop().unwrap()),
+            _ => Ok(FilterExpression::Xor(terms)),
+        }
+    }
+}
+
+fn integers<const N: usize>(s: &str) -> Result<[usize; N], String> {
+    let values = s
+        .split_whitespace()
+        .map(|v| v.parse::<usize>().map_err(|e| format!("`{}`: {}", v, e)))
+        .collect::<Result<Vec<_>, _>>()?;
+    let len = values.len();
+    values
+        .try_into()
+        .map_err(|_| format!("expected {} integers, found {}", N, len))
+}
+
+fn anf(s: &str, offset: usize) -> Result<FilterExpression, String> {
+    let mut monomials = Vec::new();
+    let mut arity = 0;
+    for monomial in s.split('+').map(str::trim) {
+        if monomial == "1" {
+            monomials.push(Vec::new());
+            continue;
+        }
+        let variables = monomial
+            .split('*')
+            .map(|v| {
+                let v = v.trim();
+                let j = v
+                    .strip_prefix('x')
+                    .and_then(|j| j.parse::<usize>().ok())
+                    .ok_or(format!("`{}` is not a variable", v))?;
+                j.checked_sub(offset)
+                    .ok_or(format!("{} belongs to a previous sub-filter", v))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        arity = variables.iter().fold(arity, |arity, &j| arity.max(j + 1));
+        monomials.push(variables);
+    }
+    let mut used = vec![false; arity];
+    for &j in monomials.iter().flatten() {
+        used[j] = true;
+    }
+    match used.iter().position(|&used| !used) {
+        Some(j) => Err(format!("x{} is not used", offset + j)),
+        None => Ok(FilterExpression::Anf { arity, monomials }),
+    }
+}
+
+fn bits(s: &str) -> Result<Vec<bool>, String> {
+    s.chars()
+        .filter(|c| !c.is_whitespace())
+        .map(|c| match c {
+            '0' => Ok(false),
+            '1' => Ok(true),
+            _ => Err(format!("unexpected `{}` in a table", c)),
+        })
+        .collect()
+}
@@ -0,0 +1,48 @@
+use crate::multiplexer::Multiplexer;
+
+/// Symmetric function of the inputs, `table[w]` being its value on the inputs of Hamming weight
+/// `w`. The accumulator holds the indicators of the Hamming weights that can still decide the
+/// result: the lowest weights are dropped once every weight they can reach evaluates to zero,
+/// and weights above the last change of `table` are merged into a single indicator.
+pub(super) fn symmetric<M: Multiplexer>(x: &[M], table: &[bool]) -> M::Bit {
+    let m = x.len();
+    assert_eq!(
+        table.len(),
+        m + 1,
+        "the table gives one value per Hamming weight"
+    );
+    let top = (1..=m)
+        .rev()
+        .find(|&w| table[w] != table[w - 1])
+        .expect("constant symmetric function");
+
+    let mut low = 0;
+    let mut acc = vec![!x[0].as_bit(), x[0].as_bit()];
+    for (i, x_i) in x.iter().enumerate().skip(1) {
+        acc.push(x_i.clone() & acc.last().unwrap().clone());
+        for j in (1..acc.len() - 1).rev() {
+            acc[j] = x_i.mux(&acc[j - 1], &acc[j]);
+        }
+        let reachable = &table[low..=(low + m - 1 - i).min(m)];
+        if low >= top || reachable.contains(&true) {
+            acc[0] = !x_i.clone() & acc.first().unwrap().clone();
+        } else {
+            acc.remove(0);
+            low += 1;
+        }
+        if low + acc.len() - 1 > top {
+            let last = acc.pop().unwrap();
+            *acc.last_mut().unwrap() ^= last;
+        }
+    }
+
+    let mut selected = acc
+        .into_iter()
+        .zip(low..)
+        .filter(|&(_, w)| table[w])
+        .map(|(indicator, _)| indicator);
+    match selected.next() {
+        Some(first) => selected.fold(first, |returnValue, indicator| returnValue ^ indicator),
+        None => x[0].constant(false),
+    }
+}
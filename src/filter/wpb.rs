@@ -0,0 +1,74 @@
+use super::expression::FilterExpression;
+
+impl FilterExpression {
+    /// Weightwise perfectly balanced function of `n = 2^k` inputs: balanced on every set of
+    /// inputs of fixed Hamming weight `0 < w < n`, zero on the null input and one on the all-ones
+    /// input. It is built recursively from `f_1(x_0, x_1) = x_0` and
+    /// `f_{k+1}(x, y) = f_k(x) + f_k(y) + x_0 x_1 ... x_{2^k - 1}`, and has degree `n / 2`. The
+    /// last input never appears in the algebraic normal form.
+    pub fn wpb(n: usize) -> Self {
+        assert!(
+            n >= 2 && n.is_power_of_two(),
+            "WPB functions are defined for powers of two"
+        );
+        let mut monomials = vec![vec![0]];
+        let mut half = 1;
+        while 2 * half < n {
+            half *= 2;
+            let shifted = monomials
+                .iter()
+                .map(|monomial| monomial.iter().map(|j| j + half).collect())
+                .collect::<Vec<_>>();
+            monomials.extend(shifted);
+            monomials.push((0..half).collect());
+        }
+        Self::Anf {
+            arity: n,
+            monomials,
+        }
+    }
+
+    /// Whether the monomials `sorted`, each one sorted, are those of `wpb(arity)` up to a
+    /// relabelling of the inputs: the only algebraic normal forms allowed to leave an input
+    /// unused. The structure is checked block by block: a block of `2m` inputs has a single
+    /// monomial of degree `m`, whose inputs form one half of the block, and every other
+    /// monomial of the block lies in one of the two halves, which are checked in turn.
+    pub(super) fn is_wpb(arity: usize, sorted: &[Vec<usize>]) -> bool {
+        if arity < 2 || !arity.is_power_of_two() || sorted.len() != arity - 1 {
+            return false;
+        }
+        let inputs = (0..arity).collect::<Vec<_>>();
+        Self::is_wpb_block(&inputs, &sorted.iter().collect::<Vec<_>>())
+    }
+
+    fn is_wpb_block(inputs: &[usize], monomials: &[&Vec<usize>]) -> bool {
+        let half = inputs.len() / 2;
+        let mut top = monomials.iter().filter(|monomial| monomial.len() == half);
+        let first = match (top.next(), top.next()) {
+            (Some(first), None) => *first,
+            _ => return false,
+        };
+        if !first.iter().all(|j| inputs.contains(j)) {
+            return false;
+        }
+        if half == 1 {
+            return monomials.len() == 1;
+        }
+        let second = inputs
+            .iter()
+            .copied()
+            .filter(|j| !first.contains(j))
+            .collect::<Vec<_>>();
+        let (mut left, mut right) = (Vec::new(), Vec::new());
+        for &monomial in monomials.iter().filter(|&&monomial| monomial != first) {
+            if monomial.iter().all(|j| first.contains(j)) {
+                left.push(monomial);
+            } else if monomial.iter().all(|j| second.contains(j)) {
+                right.push(monomial);
+            } else {
+                return false;
+            }
+        }
+        Self::is_wpb_block(first, &left) && Self::is_wpb_block(&second, &right)
+    }
+}
@@ -0,0 +1,20 @@
+use super::xor_thr::threshold;
+use crate::multiplexer::Multiplexer;
+
+/// Majority of an odd number of inputs, evaluated with the CMUX accumulator of `threshold`.
+pub(super) fn majority<M: Multiplexer>(x: &[M]) -> M::Bit {
+    assert!(
+        x.len() % 2 == 1,
+        "majority is defined over an odd number of inputs"
+    );
+    threshold(x, x.len() / 2 + 1)
+}
+
+pub(super) fn xor_maj<M: Multiplexer>(x: &[M], k: usize, m: usize) -> M::Bit {
+    let mut returnValue = x[0].as_bit();
+
+    for v in x[1..k].iter() {
+        returnValue ^= v.as_bit();
+    }
+    returnValue ^ majority(&x[k..k + m])
+}
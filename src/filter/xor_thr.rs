@@ -1,23 +1,15 @@
+use super::symmetric::symmetric;
 use crate::multiplexer::Multiplexer;
 
-fn threshold<M: Multiplexer>(x: &[M], d: usize) -> M::Bit {
-    let mut acc = vec![!x[0].as_bit(), x[0].as_bit()];
-    for (i, x_i) in x.iter().enumerate().skip(1) {
-        acc.push(x_i.clone() & acc.last().unwrap().clone());
-        for j in (1..acc.len() - 1).rev() {
-            acc[j] = x_i.mux(&acc[j - 1], &acc[j]);
-        }
-        if i < d - 1 {
-            acc[0] = !x_i.clone() & acc.first().unwrap().clone();
-        } else {
-            acc.remove(0);
-            if i > d - 1 {
-                let last = acc.pop().unwrap();
-                *acc.last_mut().unwrap() ^= last;
-            }
-        }
-    }
-    acc[0].clone()
+/// Whether at least `d` of the inputs are set, as the symmetric function whose value switches
+/// from zero to one at weight `d`.
+pub(super) fn threshold<M: Multiplexer>(x: &[M], d: usize) -> M::Bit {
+    assert!(
+        1 <= d && d <= x.len(),
+        "the threshold must lie between one and the number of inputs"
+    );
+    let table = (0..=x.len()).map(|w| w >= d).collect::<Vec<_>>();
+    symmetric(x, &table)
 }
 
 pub(super) fn xor_thr<M: Multiplexer>(x: &[M], k: usize, d: usize) -> M::Bit {
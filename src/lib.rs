@@ -2,14 +2,30 @@
 #![warn(clippy::nursery)]
 #![allow(non_snake_case)]
 
+mod analysis;
 mod bit;
+mod circuit;
 mod encrypter;
 mod filter;
 mod multiplexer;
+mod noise;
+mod prng;
 mod symmetric_key;
 
-pub use bit::{Bit, EncryptedBit};
-pub use encrypter::{Encrypter, SystemParameters};
-pub use multiplexer::{EncryptedKeyBit, Multiplexer};
+pub use analysis::{FilterProperties, SecurityEstimate};
+pub use bit::{Bit, CountingBit, EncryptedBit, ShadowBit, SimulatedBit, SymbolicBit};
+pub use circuit::{Circuit, Gate};
+pub use encrypter::{
+    Ciphertext, Encrypter, KeyBundle, KeystreamReader, KeystreamWriter, PacketError,
+    ParameterError, ParameterSet, SystemParameters, ToyParameters,
+};
+pub use filter::{Filter, FilterError, FilterExpression, FilterType};
+pub use multiplexer::{
+    CountingKeyBit, Divergence, EncryptedKeyBit, Multiplexer, Operation, OperationCount,
+    ShadowKeyBit, SimulatedKeyBit, SymbolicKeyBit,
+};
+pub use noise::{NoiseEstimate, NoiseMeasurement, NoiseModel};
+pub use prng::{AesCtrPrng, ChaCha20Prng, KeystreamPrng, PrngType, ReferencePrng, Shake128Prng};
+pub use symmetric_key::Nonce;
 
 pub type Torus = u64;
@@ -56,6 +56,10 @@ impl Multiplexer for bool {
         *self
     }
 
+    fn constant(&self, value: bool) -> bool {
+        value
+    }
+
     fn not_inplace(&mut self) {
         *self = !*self;
     }
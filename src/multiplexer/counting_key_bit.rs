@@ -0,0 +1,237 @@
+use crate::{multiplexer::Multiplexer, CountingBit, Torus};
+use concrete_commons::{
+    dispersion::StandardDev,
+    key_kinds::BinaryKeyKind,
+    parameters::{DecompositionBaseLog, DecompositionLevelCount, GlweSize, PolynomialSize},
+};
+use concrete_core::crypto::secret::GlweSecretKey;
+use serde::{Deserialize, Serialize};
+use std::{
+    cell::RefCell,
+    fmt,
+    ops::{BitAnd, Not},
+    rc::Rc,
+};
+
+/// Homomorphic operations issued while evaluating the filters, as counted by `CountingKeyBit`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OperationCount {
+    /// External products `key & bit`, besides the ones of the CMUXes.
+    pub external_products: usize,
+    pub cmuxes: usize,
+    /// Additions of GLWE ciphertexts, for the xors.
+    pub additions: usize,
+    /// Negations of GLWE ciphertexts.
+    pub negations: usize,
+    /// Negations of GGSW key bits, for the whitening and in the threshold accumulators.
+    pub key_negations: usize,
+    pub as_bits: usize,
+    /// Trivial encryptions of constants.
+    pub constants: usize,
+    pub ggsw_clones: usize,
+    /// Multiplicative depth of the bit: largest number of external products, CMUX included,
+    /// on a path from the key bits.
+    pub depth: usize,
+}
+
+impl OperationCount {
+    /// External products, including the one of each CMUX, which dominate the cost.
+    pub fn total_external_products(&self) -> usize {
+        self.external_products + self.cmuxes
+    }
+}
+
+impl fmt::Display for OperationCount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} external products, {} CMUX, {} additions, {} negations, {} key negations, \
+             {} as_bit, {} constants, {} GGSW clones, depth {}",
+            self.external_products,
+            self.cmuxes,
+            self.additions,
+            self.negations,
+            self.key_negations,
+            self.as_bits,
+            self.constants,
+            self.ggsw_clones,
+            self.depth
+        )
+    }
+}
+
+/// Key bit that evaluates nothing, and counts the operations the filters issue instead. The
+/// key bits of `CountingKeyBit::key`, and the bits computed from them, share their count.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct CountingKeyBit {
+    #[serde(skip)]
+    count: Rc<RefCell<OperationCount>>,
+}
+
+impl Clone for CountingKeyBit {
+    fn clone(&self) -> Self {
+        self.count.borrow_mut().ggsw_clones += 1;
+        Self {
+            count: Rc::clone(&self.count),
+        }
+    }
+}
+
+impl BitAnd<CountingBit> for CountingKeyBit {
+    type Output = CountingBit;
+
+    fn bitand(self, rhs: CountingBit) -> Self::Output {
+        self.count.borrow_mut().external_products += 1;
+        CountingBit::new(rhs.depth() + 1, Rc::clone(&self.count))
+    }
+}
+
+impl Not for CountingKeyBit {
+    type Output = Self;
+
+    fn not(mut self) -> Self::Output {
+        self.not_inplace();
+        self
+    }
+}
+
+impl CountingKeyBit {
+    /// Symmetric key of `size` bits, whose operations are counted together.
+    pub fn key(size: usize) -> Vec<Self> {
+        let count = Rc::new(RefCell::new(OperationCount::default()));
+        (0..size)
+            .map(|_| Self {
+                count: Rc::clone(&count),
+            })
+            .collect()
+    }
+
+    /// Operations counted so far.
+    pub fn count(&self) -> OperationCount {
+        *self.count.borrow()
+    }
+
+    /// Restarts the shared count, for instance to leave out the setup of an `Encrypter`.
+    pub fn reset(&self) {
+        *self.count.borrow_mut() = OperationCount::default();
+    }
+}
+
+impl Multiplexer for CountingKeyBit {
+    type Bit = CountingBit;
+
+    fn zero(
+        _sk: Option<&GlweSecretKey<BinaryKeyKind, Vec<Torus>>>,
+        _decomp_level: Option<DecompositionLevelCount>,
+        _decomp_base_log: Option<DecompositionBaseLog>,
+        _noise_parameters: Option<StandardDev>,
+    ) -> Self {
+        Self::default()
+    }
+
+    fn one(
+        _sk: Option<&GlweSecretKey<BinaryKeyKind, Vec<Torus>>>,
+        _decomp_level: Option<DecompositionLevelCount>,
+        _decomp_base_log: Option<DecompositionBaseLog>,
+        _noise_parameters: Option<StandardDev>,
+    ) -> Self {
+        Self::default()
+    }
+
+    fn zero_with_fhe_parameters(
+        _poly_size: Option<PolynomialSize>,
+        _size: Option<GlweSize>,
+        _decomp_level: Option<DecompositionLevelCount>,
+        _decomp_base_log: Option<DecompositionBaseLog>,
+    ) -> Self {
+        Self::default()
+    }
+
+    fn one_with_fhe_parameters(
+        _poly_size: Option<PolynomialSize>,
+        _size: Option<GlweSize>,
+        _decomp_level: Option<DecompositionLevelCount>,
+        _decomp_base_log: Option<DecompositionBaseLog>,
+    ) -> Self {
+        Self::default()
+    }
+
+    fn mux(&self, o1: &CountingBit, o0: &CountingBit) -> CountingBit {
+        self.count.borrow_mut().cmuxes += 1;
+        CountingBit::new(o1.depth().max(o0.depth()) + 1, Rc::clone(&self.count))
+    }
+
+    fn as_bit(&self) -> CountingBit {
+        self.count.borrow_mut().as_bits += 1;
+        CountingBit::new(0, Rc::clone(&self.count))
+    }
+
+    fn constant(&self, _value: bool) -> CountingBit {
+        self.count.borrow_mut().constants += 1;
+        CountingBit::new(0, Rc::clone(&self.count))
+    }
+
+    fn not_inplace(&mut self) {
+        self.count.borrow_mut().key_negations += 1;
+    }
+
+    fn descriptor() -> String {
+        "counting".to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        filter::{Filter, FilterType},
+        SystemParameters,
+    };
+
+    #[test]
+    fn dsm() {
+        let key = CountingKeyBit::key(5);
+        let bit = Filter::new(FilterType::DSM, &[2, 0, 1]).call(&key);
+        assert_eq!(
+            bit.count(),
+            OperationCount {
+                external_products: 2,
+                additions: 2,
+                as_bits: 3,
+                ggsw_clones: 2,
+                depth: 2,
+                ..OperationCount::default()
+            }
+        );
+    }
+
+    #[test]
+    fn xor_thr() {
+        // Two inputs in the linear part, and the accumulator of a threshold of 3 out of 4
+        // inputs, which starts from the first input and its negation.
+        let key = CountingKeyBit::key(6);
+        assert_eq!(
+            Filter::new(FilterType::XorThr, &[2, 3]).call(&key).count(),
+            OperationCount {
+                external_products: 3,
+                cmuxes: 3,
+                additions: 3,
+                negations: 1,
+                as_bits: 4,
+                ggsw_clones: 3,
+                depth: 3,
+                ..OperationCount::default()
+            }
+        );
+    }
+
+    #[test]
+    fn keystream() {
+        // Selecting and whitening the inputs clones every one of them.
+        let set = SystemParameters::n144.parameter_set();
+        let count = set.operation_count();
+        assert!(count.ggsw_clones >= set.n);
+        assert!(count.key_negations > 0 && count.key_negations <= set.n);
+        assert_eq!(count.as_bits, 81 + 2);
+    }
+}
@@ -1,9 +1,10 @@
-use crate::{multiplexer::Multiplexer, EncryptedBit, Torus};
+use crate::{multiplexer::Multiplexer, Bit, EncryptedBit, Torus};
 use concrete_commons::{
     dispersion::StandardDev,
     key_kinds::BinaryKeyKind,
     parameters::{
-        DecompositionBaseLog, DecompositionLevelCount, GlweSize, LweDimension, PolynomialSize,
+        DecompositionBaseLog, DecompositionLevelCount, GlweDimension, GlweSize, LweDimension,
+        PolynomialSize,
     },
 };
 use concrete_core::{
@@ -58,6 +59,54 @@ impl Not for EncryptedKeyBit {
     }
 }
 
+impl EncryptedKeyBit {
+    /// Encrypts a clear symmetric key bit by bit under the FHE secret key `sk`.
+    pub fn encrypt_key(
+        key: &[bool],
+        sk: &GlweSecretKey<BinaryKeyKind, Vec<Torus>>,
+        decomp_level: DecompositionLevelCount,
+        decomp_base_log: DecompositionBaseLog,
+        noise_parameters: StandardDev,
+    ) -> Vec<Self> {
+        key.iter()
+            .map(|&bit| {
+                if bit {
+                    Self::one(
+                        Some(sk),
+                        Some(decomp_level),
+                        Some(decomp_base_log),
+                        Some(noise_parameters),
+                    )
+                } else {
+                    Self::zero(
+                        Some(sk),
+                        Some(decomp_level),
+                        Some(decomp_base_log),
+                        Some(noise_parameters),
+                    )
+                }
+            })
+            .collect()
+    }
+
+    /// GLWE dimension, polynomial size and decomposition the key bit was encrypted with.
+    pub fn fhe_parameters(
+        &self,
+    ) -> (
+        GlweDimension,
+        PolynomialSize,
+        DecompositionBaseLog,
+        DecompositionLevelCount,
+    ) {
+        (
+            self.0.glwe_size().to_glwe_dimension(),
+            self.0.polynomial_size(),
+            self.0.base_log(),
+            self.0.level_count(),
+        )
+    }
+}
+
 impl Multiplexer for EncryptedKeyBit {
     type Bit = EncryptedBit;
     fn zero(
@@ -285,6 +334,15 @@ impl Multiplexer for EncryptedKeyBit {
         EncryptedBit::new(output)
     }
 
+    fn constant(&self, value: bool) -> Self::Bit {
+        let (poly_size, size) = (Some(self.0.polynomial_size()), Some(self.0.glwe_size()));
+        if value {
+            EncryptedBit::one(poly_size, size)
+        } else {
+            EncryptedBit::zero(poly_size, size)
+        }
+    }
+
     fn not_inplace(&mut self) {
         let gadget = Self::one_with_fhe_parameters(
             Some(self.0.polynomial_size()),
@@ -373,6 +431,32 @@ mod test {
         }
     }
 
+    #[test]
+    fn encrypt_key() {
+        let mut generator = SecretRandomGenerator::new(None);
+        let sk =
+            GlweSecretKey::generate_binary(GlweDimension(1), PolynomialSize(1024), &mut generator);
+        let key = [true, false, false, true, true, false, true, false];
+        let encrypted_key = EncryptedKeyBit::encrypt_key(
+            &key,
+            &sk,
+            DecompositionLevelCount(2),
+            DecompositionBaseLog(1),
+            StandardDev::from_standard_dev(2_f64.powf(-14.)),
+        );
+        for (bit, encrypted_bit) in key.iter().zip(encrypted_key.iter()) {
+            let mut decrypted = PlaintextList::allocate(0, PlaintextCount(1024));
+            sk.decrypt_glwe(&mut decrypted, encrypted_bit.as_bit().as_glwe());
+            let mut decoded = decrypted.as_tensor().as_slice()[0] >> (Torus::BITS as usize - 2);
+            if decoded % 2 == 1 {
+                decoded += 2;
+            }
+            decoded >>= 1;
+            decoded %= 2;
+            assert_eq!(decoded == 1, *bit);
+        }
+    }
+
     #[test]
     fn ggsw_not() {
         let mut generator = SecretRandomGenerator::new(None);
@@ -1,5 +1,9 @@
 mod bit_bool;
+mod counting_key_bit;
 mod encrypted_key_bit;
+mod shadow_key_bit;
+mod simulated_key_bit;
+mod symbolic_key_bit;
 
 use crate::{Bit, Torus};
 use concrete_commons::{
@@ -11,7 +15,12 @@ use concrete_core::crypto::secret::GlweSecretKey;
 use serde::{de::DeserializeOwned, Serialize};
 use std::ops::{BitAnd, Not};
 
+pub use counting_key_bit::{CountingKeyBit, OperationCount};
 pub use encrypted_key_bit::EncryptedKeyBit;
+pub(crate) use shadow_key_bit::{either, Shadow};
+pub use shadow_key_bit::{Divergence, Operation, ShadowKeyBit};
+pub use simulated_key_bit::SimulatedKeyBit;
+pub use symbolic_key_bit::SymbolicKeyBit;
 
 pub trait Multiplexer:
     Clone + Not<Output = Self> + BitAnd<Self::Bit, Output = Self::Bit> + Serialize + DeserializeOwned
@@ -44,6 +53,8 @@ pub trait Multiplexer:
 
     fn mux(&self, o1: &Self::Bit, o0: &Self::Bit) -> Self::Bit;
     fn as_bit(&self) -> Self::Bit;
+    /// Trivial encryption of `value`, with the same parameters as `self`.
+    fn constant(&self, value: bool) -> Self::Bit;
 
     fn not_inplace(&mut self);
 
@@ -0,0 +1,299 @@
+use crate::{
+    multiplexer::Multiplexer,
+    noise::{self, NoiseMeasurement},
+    EncryptedBit, EncryptedKeyBit, ShadowBit, Torus,
+};
+use concrete_commons::{
+    dispersion::StandardDev,
+    key_kinds::BinaryKeyKind,
+    parameters::{DecompositionBaseLog, DecompositionLevelCount, GlweSize, PolynomialSize},
+};
+use concrete_core::crypto::secret::GlweSecretKey;
+use serde::{Deserialize, Serialize};
+use std::{
+    cell::{Cell, RefCell},
+    fmt,
+    ops::{BitAnd, Not},
+    rc::Rc,
+};
+
+/// Homomorphic operation checked by `ShadowKeyBit` and `ShadowBit`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Operation {
+    AsBit,
+    ExternalProduct,
+    Cmux,
+    Addition,
+    Negation,
+    KeyNegation,
+    Constant,
+}
+
+impl fmt::Display for Operation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::AsBit => "as_bit",
+            Self::ExternalProduct => "external product",
+            Self::Cmux => "CMUX",
+            Self::Addition => "addition",
+            Self::Negation => "negation",
+            Self::KeyNegation => "key negation",
+            Self::Constant => "constant",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// First operation whose encrypted result decrypts to another value than the clear one.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Divergence {
+    /// Number of operations checked before this one, since the key was built.
+    pub index: usize,
+    pub operation: Operation,
+    /// Value of the clear evaluation, which the ciphertext should encrypt.
+    pub expected: bool,
+    /// Phase errors of the result against `expected`.
+    pub noise: NoiseMeasurement,
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "operation {} ({}) should give {}: {}",
+            self.index, self.operation, self.expected, self.noise
+        )
+    }
+}
+
+/// GLWE secret key and checks shared by a shadowed key and the bits computed from it.
+pub(crate) struct Shadow {
+    sk: GlweSecretKey<BinaryKeyKind, Vec<Torus>>,
+    operations: Cell<usize>,
+    divergence: RefCell<Option<Divergence>>,
+}
+
+impl Shadow {
+    /// Decrypts the result `bit` of `operation`, and records it if it does not encrypt `value`
+    /// and is the first one to. Once a divergence is found, later operations are only counted.
+    pub(crate) fn check(&self, operation: Operation, bit: &EncryptedBit, value: bool) {
+        let index = self.operations.get();
+        self.operations.set(index + 1);
+        if self.divergence.borrow().is_some() {
+            return;
+        }
+        let errors = noise::phase_errors(&self.sk, bit, value);
+        if errors[0].abs() >= 0.25 {
+            let mut noise = NoiseMeasurement::new();
+            for error in errors {
+                noise.add_error(error);
+            }
+            *self.divergence.borrow_mut() = Some(Divergence {
+                index,
+                operation,
+                expected: value,
+                noise,
+            });
+        }
+    }
+
+    pub(crate) fn divergence(&self) -> Option<Divergence> {
+        self.divergence.borrow().clone()
+    }
+}
+
+/// Shadow of the operands of an operation, for the ones built without a secret key.
+pub(crate) fn either(a: &Option<Rc<Shadow>>, b: &Option<Rc<Shadow>>) -> Option<Rc<Shadow>> {
+    a.as_ref().or_else(|| b.as_ref()).cloned()
+}
+
+/// Debugging key bit that runs the encrypted evaluation and the clear one in lockstep. Given
+/// the GLWE secret key, it decrypts the result of every operation, and keeps the first one that
+/// does not match the clear value, with its noise. The key bits of `ShadowKeyBit::from_key`
+/// and the bits computed from them share their checks.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ShadowKeyBit {
+    key: EncryptedKeyBit,
+    value: bool,
+    #[serde(skip)]
+    shadow: Option<Rc<Shadow>>,
+}
+
+impl BitAnd<ShadowBit> for ShadowKeyBit {
+    type Output = ShadowBit;
+
+    fn bitand(self, rhs: ShadowBit) -> Self::Output {
+        let shadow = either(&self.shadow, rhs.shadow());
+        let value = self.value & rhs.value();
+        ShadowBit::checked(
+            Operation::ExternalProduct,
+            self.key & rhs.into_bit(),
+            value,
+            shadow,
+        )
+    }
+}
+
+impl Not for ShadowKeyBit {
+    type Output = Self;
+
+    fn not(mut self) -> Self::Output {
+        self.not_inplace();
+        self
+    }
+}
+
+impl ShadowKeyBit {
+    /// Pairs the encrypted symmetric key `key` with its clear value `clear`, to be checked
+    /// under the FHE secret key `sk`.
+    pub fn from_key(
+        key: Vec<EncryptedKeyBit>,
+        clear: &[bool],
+        sk: &GlweSecretKey<BinaryKeyKind, Vec<Torus>>,
+    ) -> Vec<Self> {
+        assert_eq!(key.len(), clear.len(), "one clear value per key bit");
+        let shadow = Rc::new(Shadow {
+            sk: sk.clone(),
+            operations: Cell::new(0),
+            divergence: RefCell::new(None),
+        });
+        key.into_iter()
+            .zip(clear)
+            .map(|(key, &value)| Self {
+                key,
+                value,
+                shadow: Some(Rc::clone(&shadow)),
+            })
+            .collect()
+    }
+
+    /// Encrypts a clear symmetric key bit by bit under the FHE secret key `sk`, see
+    /// `EncryptedKeyBit::encrypt_key`.
+    pub fn encrypt_key(
+        key: &[bool],
+        sk: &GlweSecretKey<BinaryKeyKind, Vec<Torus>>,
+        decomp_level: DecompositionLevelCount,
+        decomp_base_log: DecompositionBaseLog,
+        noise_parameters: StandardDev,
+    ) -> Vec<Self> {
+        let encrypted =
+            EncryptedKeyBit::encrypt_key(key, sk, decomp_level, decomp_base_log, noise_parameters);
+        Self::from_key(encrypted, key, sk)
+    }
+
+    pub fn value(&self) -> bool {
+        self.value
+    }
+
+    /// First operation that went wrong so far, over the whole key.
+    pub fn divergence(&self) -> Option<Divergence> {
+        self.shadow.as_ref().and_then(|shadow| shadow.divergence())
+    }
+
+    /// Number of operations checked so far, over the whole key.
+    pub fn operations(&self) -> usize {
+        self.shadow
+            .as_ref()
+            .map_or(0, |shadow| shadow.operations.get())
+    }
+
+    fn unchecked(key: EncryptedKeyBit, value: bool) -> Self {
+        Self {
+            key,
+            value,
+            shadow: None,
+        }
+    }
+}
+
+impl Multiplexer for ShadowKeyBit {
+    type Bit = ShadowBit;
+
+    fn zero(
+        sk: Option<&GlweSecretKey<BinaryKeyKind, Vec<Torus>>>,
+        decomp_level: Option<DecompositionLevelCount>,
+        decomp_base_log: Option<DecompositionBaseLog>,
+        noise_parameters: Option<StandardDev>,
+    ) -> Self {
+        let key = EncryptedKeyBit::zero(sk, decomp_level, decomp_base_log, noise_parameters);
+        Self::unchecked(key, false)
+    }
+
+    fn one(
+        sk: Option<&GlweSecretKey<BinaryKeyKind, Vec<Torus>>>,
+        decomp_level: Option<DecompositionLevelCount>,
+        decomp_base_log: Option<DecompositionBaseLog>,
+        noise_parameters: Option<StandardDev>,
+    ) -> Self {
+        let key = EncryptedKeyBit::one(sk, decomp_level, decomp_base_log, noise_parameters);
+        Self::unchecked(key, true)
+    }
+
+    fn zero_with_fhe_parameters(
+        poly_size: Option<PolynomialSize>,
+        size: Option<GlweSize>,
+        decomp_level: Option<DecompositionLevelCount>,
+        decomp_base_log: Option<DecompositionBaseLog>,
+    ) -> Self {
+        let key = EncryptedKeyBit::zero_with_fhe_parameters(
+            poly_size,
+            size,
+            decomp_level,
+            decomp_base_log,
+        );
+        Self::unchecked(key, false)
+    }
+
+    fn one_with_fhe_parameters(
+        poly_size: Option<PolynomialSize>,
+        size: Option<GlweSize>,
+        decomp_level: Option<DecompositionLevelCount>,
+        decomp_base_log: Option<DecompositionBaseLog>,
+    ) -> Self {
+        let key = EncryptedKeyBit::one_with_fhe_parameters(
+            poly_size,
+            size,
+            decomp_level,
+            decomp_base_log,
+        );
+        Self::unchecked(key, true)
+    }
+
+    fn mux(&self, o1: &ShadowBit, o0: &ShadowBit) -> ShadowBit {
+        let shadow = either(&self.shadow, &either(o1.shadow(), o0.shadow()));
+        let value = if self.value { o1.value() } else { o0.value() };
+        let bit = self.key.mux(o1.bit(), o0.bit());
+        ShadowBit::checked(Operation::Cmux, bit, value, shadow)
+    }
+
+    fn as_bit(&self) -> ShadowBit {
+        ShadowBit::checked(
+            Operation::AsBit,
+            self.key.as_bit(),
+            self.value,
+            self.shadow.clone(),
+        )
+    }
+
+    fn constant(&self, value: bool) -> ShadowBit {
+        ShadowBit::checked(
+            Operation::Constant,
+            self.key.constant(value),
+            value,
+            self.shadow.clone(),
+        )
+    }
+
+    /// Also checks the negated GGSW, through its `as_bit`.
+    fn not_inplace(&mut self) {
+        self.key.not_inplace();
+        self.value = !self.value;
+        if let Some(shadow) = &self.shadow {
+            shadow.check(Operation::KeyNegation, &self.key.as_bit(), self.value);
+        }
+    }
+
+    fn descriptor() -> String {
+        "shadow".to_string()
+    }
+}
@@ -0,0 +1,148 @@
+use crate::{multiplexer::Multiplexer, NoiseModel, SimulatedBit, Torus};
+use concrete_commons::{
+    dispersion::{DispersionParameter, StandardDev},
+    key_kinds::BinaryKeyKind,
+    parameters::{DecompositionBaseLog, DecompositionLevelCount, GlweSize, PolynomialSize},
+};
+use concrete_core::crypto::secret::GlweSecretKey;
+use serde::{Deserialize, Serialize};
+use std::ops::{BitAnd, Not};
+
+/// Stand-in for an `EncryptedKeyBit`: the clear key bit, along with the variance of its noise
+/// and the model of the operations it takes part in. Running the filters over it gives, at
+/// the speed of the clear evaluation, the noise of every transciphered bit.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SimulatedKeyBit {
+    model: NoiseModel,
+    value: bool,
+    variance: f64,
+}
+
+impl BitAnd<SimulatedBit> for SimulatedKeyBit {
+    type Output = SimulatedBit;
+
+    fn bitand(self, rhs: SimulatedBit) -> Self::Output {
+        SimulatedBit::new(
+            self.value & rhs.value(),
+            self.model
+                .external_product(self.variance, self.value, rhs.variance()),
+        )
+    }
+}
+
+impl Not for SimulatedKeyBit {
+    type Output = Self;
+
+    fn not(mut self) -> Self::Output {
+        self.not_inplace();
+        self
+    }
+}
+
+impl SimulatedKeyBit {
+    /// Freshly encrypted key bit.
+    pub fn new(model: NoiseModel, value: bool) -> Self {
+        Self {
+            model,
+            value,
+            variance: model.fresh(),
+        }
+    }
+
+    /// Simulates the encryption of a clear symmetric key bit by bit.
+    pub fn encrypt_key(key: &[bool], model: NoiseModel) -> Vec<Self> {
+        key.iter().map(|&bit| Self::new(model, bit)).collect()
+    }
+
+    pub fn value(&self) -> bool {
+        self.value
+    }
+
+    pub fn variance(&self) -> f64 {
+        self.variance
+    }
+
+    pub fn model(&self) -> &NoiseModel {
+        &self.model
+    }
+}
+
+impl Multiplexer for SimulatedKeyBit {
+    type Bit = SimulatedBit;
+
+    fn zero(
+        sk: Option<&GlweSecretKey<BinaryKeyKind, Vec<Torus>>>,
+        decomp_level: Option<DecompositionLevelCount>,
+        decomp_base_log: Option<DecompositionBaseLog>,
+        noise_parameters: Option<StandardDev>,
+    ) -> Self {
+        let sk = sk.unwrap();
+        let model = NoiseModel::new(
+            sk.key_size().0,
+            sk.polynomial_size().0,
+            decomp_base_log.unwrap().0,
+            decomp_level.unwrap().0,
+            noise_parameters.unwrap().get_standard_dev(),
+        );
+        Self::new(model, false)
+    }
+
+    fn one(
+        sk: Option<&GlweSecretKey<BinaryKeyKind, Vec<Torus>>>,
+        decomp_level: Option<DecompositionLevelCount>,
+        decomp_base_log: Option<DecompositionBaseLog>,
+        noise_parameters: Option<StandardDev>,
+    ) -> Self {
+        !Self::zero(sk, decomp_level, decomp_base_log, noise_parameters)
+    }
+
+    /// Noiseless key bit, as the trivial encryptions of `EncryptedKeyBit`.
+    fn zero_with_fhe_parameters(
+        poly_size: Option<PolynomialSize>,
+        size: Option<GlweSize>,
+        decomp_level: Option<DecompositionLevelCount>,
+        decomp_base_log: Option<DecompositionBaseLog>,
+    ) -> Self {
+        let model = NoiseModel::new(
+            size.unwrap().to_glwe_dimension().0,
+            poly_size.unwrap().0,
+            decomp_base_log.unwrap().0,
+            decomp_level.unwrap().0,
+            0.,
+        );
+        Self::new(model, false)
+    }
+
+    fn one_with_fhe_parameters(
+        poly_size: Option<PolynomialSize>,
+        size: Option<GlweSize>,
+        decomp_level: Option<DecompositionLevelCount>,
+        decomp_base_log: Option<DecompositionBaseLog>,
+    ) -> Self {
+        !Self::zero_with_fhe_parameters(poly_size, size, decomp_level, decomp_base_log)
+    }
+
+    fn mux(&self, o1: &SimulatedBit, o0: &SimulatedBit) -> SimulatedBit {
+        SimulatedBit::new(
+            if self.value { o1.value() } else { o0.value() },
+            self.model
+                .cmux(self.variance, self.value, o1.variance(), o0.variance()),
+        )
+    }
+
+    fn as_bit(&self) -> SimulatedBit {
+        SimulatedBit::new(self.value, self.model.as_bit(self.variance))
+    }
+
+    fn constant(&self, value: bool) -> SimulatedBit {
+        SimulatedBit::new(value, 0.)
+    }
+
+    fn not_inplace(&mut self) {
+        self.value = !self.value;
+    }
+
+    fn descriptor() -> String {
+        "simulated".to_string()
+    }
+}
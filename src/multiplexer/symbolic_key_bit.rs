@@ -0,0 +1,146 @@
+use crate::{
+    circuit::{Circuit, Gate},
+    multiplexer::Multiplexer,
+    SymbolicBit, Torus,
+};
+use concrete_commons::{
+    dispersion::StandardDev,
+    key_kinds::BinaryKeyKind,
+    parameters::{DecompositionBaseLog, DecompositionLevelCount, GlweSize, PolynomialSize},
+};
+use concrete_core::crypto::secret::GlweSecretKey;
+use serde::{Deserialize, Serialize};
+use std::{
+    cell::RefCell,
+    ops::{BitAnd, Not},
+    rc::Rc,
+};
+
+/// Key bit that records the Boolean circuit the filters compute, instead of evaluating it. The
+/// key bits of `SymbolicKeyBit::key` are the inputs of a shared circuit, where each operation
+/// adds a gate: the whitening negations, the external products as AND gates, the CMUX as
+/// multiplexers and the xors.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SymbolicKeyBit {
+    wire: usize,
+    #[serde(skip)]
+    circuit: Rc<RefCell<Circuit>>,
+}
+
+impl BitAnd<SymbolicBit> for SymbolicKeyBit {
+    type Output = SymbolicBit;
+
+    fn bitand(self, rhs: SymbolicBit) -> Self::Output {
+        rhs.assert_in(&self.circuit);
+        let wire = self.push(Gate::And(self.wire, rhs.wire()));
+        SymbolicBit::new(wire, Rc::clone(&self.circuit))
+    }
+}
+
+impl Not for SymbolicKeyBit {
+    type Output = Self;
+
+    fn not(mut self) -> Self::Output {
+        self.not_inplace();
+        self
+    }
+}
+
+impl SymbolicKeyBit {
+    /// Symmetric key of `size` bits, the inputs of a new circuit.
+    pub fn key(size: usize) -> Vec<Self> {
+        let circuit = Rc::new(RefCell::new(Circuit::new(size)));
+        (0..size)
+            .map(|wire| Self {
+                wire,
+                circuit: Rc::clone(&circuit),
+            })
+            .collect()
+    }
+
+    /// Key bit of constant value, in a circuit of its own: it cannot be combined with the bits of
+    /// another circuit.
+    fn constant_key(value: bool) -> Self {
+        let mut circuit = Circuit::new(0);
+        let wire = circuit.push(Gate::Const(value));
+        Self {
+            wire,
+            circuit: Rc::new(RefCell::new(circuit)),
+        }
+    }
+
+    pub fn wire(&self) -> usize {
+        self.wire
+    }
+
+    fn push(&self, gate: Gate) -> usize {
+        self.circuit.borrow_mut().push(gate)
+    }
+}
+
+impl Multiplexer for SymbolicKeyBit {
+    type Bit = SymbolicBit;
+
+    fn zero(
+        _sk: Option<&GlweSecretKey<BinaryKeyKind, Vec<Torus>>>,
+        _decomp_level: Option<DecompositionLevelCount>,
+        _decomp_base_log: Option<DecompositionBaseLog>,
+        _noise_parameters: Option<StandardDev>,
+    ) -> Self {
+        Self::constant_key(false)
+    }
+
+    fn one(
+        _sk: Option<&GlweSecretKey<BinaryKeyKind, Vec<Torus>>>,
+        _decomp_level: Option<DecompositionLevelCount>,
+        _decomp_base_log: Option<DecompositionBaseLog>,
+        _noise_parameters: Option<StandardDev>,
+    ) -> Self {
+        Self::constant_key(true)
+    }
+
+    fn zero_with_fhe_parameters(
+        _poly_size: Option<PolynomialSize>,
+        _size: Option<GlweSize>,
+        _decomp_level: Option<DecompositionLevelCount>,
+        _decomp_base_log: Option<DecompositionBaseLog>,
+    ) -> Self {
+        Self::constant_key(false)
+    }
+
+    fn one_with_fhe_parameters(
+        _poly_size: Option<PolynomialSize>,
+        _size: Option<GlweSize>,
+        _decomp_level: Option<DecompositionLevelCount>,
+        _decomp_base_log: Option<DecompositionBaseLog>,
+    ) -> Self {
+        Self::constant_key(true)
+    }
+
+    fn mux(&self, o1: &SymbolicBit, o0: &SymbolicBit) -> SymbolicBit {
+        o1.assert_in(&self.circuit);
+        o0.assert_in(&self.circuit);
+        let wire = self.push(Gate::Mux {
+            select: self.wire,
+            one: o1.wire(),
+            zero: o0.wire(),
+        });
+        SymbolicBit::new(wire, Rc::clone(&self.circuit))
+    }
+
+    fn as_bit(&self) -> SymbolicBit {
+        SymbolicBit::new(self.wire, Rc::clone(&self.circuit))
+    }
+
+    fn constant(&self, value: bool) -> SymbolicBit {
+        SymbolicBit::new(self.push(Gate::Const(value)), Rc::clone(&self.circuit))
+    }
+
+    fn not_inplace(&mut self) {
+        self.wire = self.push(Gate::Inv(self.wire));
+    }
+
+    fn descriptor() -> String {
+        "symbolic".to_string()
+    }
+}
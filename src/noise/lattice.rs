@@ -0,0 +1,51 @@
+//! Hardness of the GLWE problem behind the FHE keys, under the primal uSVP attack.
+
+use std::f64::consts::{E, PI};
+
+/// Root Hermite factor reached by BKZ with blocks of size `beta`.
+fn delta(beta: f64) -> f64 {
+    ((PI * beta).powf(1. / beta) * beta / (2. * PI * E)).powf(1. / (2. * (beta - 1.)))
+}
+
+/// Core-SVP cost `0.292 beta`, in bits, of the primal uSVP attack on LWE in dimension `n`
+/// with a binary secret and a Gaussian error of standard deviation `std_dev` on the torus.
+/// The attack embeds `m` samples in a lattice of dimension `d = m + n + 1`, scales the secret
+/// to the size of the error, and succeeds once `std_dev sqrt(beta) <= delta^(2 beta - d)
+/// vol^(1 / d)`. The modulus is `2^64`, and the attacker picks the best `m`.
+pub(crate) fn primal_usvp(n: usize, std_dev: f64) -> f64 {
+    let log_q = 64. * std::f64::consts::LN_2;
+    // Error and secret, the latter of standard deviation 1/2, in units of the modulus.
+    let log_error = std_dev.ln() + log_q;
+    let log_scale = log_error - 0.5_f64.ln();
+    let succeeds = |beta: usize, m: usize| {
+        let (beta, d) = (beta as f64, (m + n + 1) as f64);
+        let log_volume = m as f64 * log_q + n as f64 * log_scale;
+        log_error + 0.5 * beta.ln() <= (2. * beta - d) * delta(beta).ln() + log_volume / d
+    };
+    let step = (n / 64).max(1);
+    let beta = (1..=4 * n / step)
+        .map(|i| i * step)
+        .filter_map(|m| {
+            let d = m + n + 1;
+            if !succeeds(d, m) {
+                return None;
+            }
+            // The success condition only gets easier as the blocks grow.
+            let (mut low, mut high) = (40, d);
+            if succeeds(low, m) {
+                return Some(low);
+            }
+            while high - low > 1 {
+                let mid = (low + high) / 2;
+                if succeeds(mid, m) {
+                    high = mid;
+                } else {
+                    low = mid;
+                }
+            }
+            Some(high)
+        })
+        .min()
+        .unwrap_or(n + 1);
+    0.292 * beta as f64
+}
@@ -0,0 +1,154 @@
+use std::fmt;
+
+use super::NoiseEstimate;
+use crate::{EncryptedBit, EncryptedKeyBit, Encrypter, FilterExpression, Torus};
+use concrete_commons::{key_kinds::BinaryKeyKind, parameters::PlaintextCount};
+use concrete_core::{
+    crypto::{encoding::PlaintextList, secret::GlweSecretKey},
+    math::tensor::{AsRefSlice, AsRefTensor},
+};
+
+/// Distribution of the phase errors of transciphered bits, decrypted with the GLWE secret key.
+/// Every coefficient of the phase is a sample: the constant one carries the bit and the others
+/// encrypt zero, all with the same noise.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct NoiseMeasurement {
+    count: usize,
+    sum: f64,
+    sum_squares: f64,
+    max: f64,
+}
+
+impl NoiseMeasurement {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Measures the noise of `bits`, which encrypt `values`.
+    pub fn from_bits(
+        sk: &GlweSecretKey<BinaryKeyKind, Vec<Torus>>,
+        bits: &[EncryptedBit],
+        values: &[bool],
+    ) -> Self {
+        assert_eq!(bits.len(), values.len(), "one expected value per bit");
+        let mut returnValue = Self::new();
+        for (bit, &value) in bits.iter().zip(values) {
+            returnValue.add(sk, bit, value);
+        }
+        returnValue
+    }
+
+    /// Noise of each sub-filter of the filter of `server`, see `Encrypter::stages`, over its
+    /// next `count` keystream bits. `client` must share the key, nonce and position of `server`,
+    /// and gives the expected values. Identical sub-filters, such as the monomials of a given
+    /// degree of a DSM filter, are measured together, in the order they first appear.
+    pub fn stages(
+        sk: &GlweSecretKey<BinaryKeyKind, Vec<Torus>>,
+        client: &mut Encrypter<bool>,
+        server: &mut Encrypter<EncryptedKeyBit>,
+        count: usize,
+    ) -> Vec<(FilterExpression, Self)> {
+        assert_eq!(
+            (client.nonce(), client.position()),
+            (server.nonce(), server.position()),
+            "the client and the server are not at the same point of the keystream"
+        );
+        let mut returnValue: Vec<(FilterExpression, Self)> = Vec::new();
+        for _ in 0..count {
+            for ((term, value), (_, bit)) in client.stages().into_iter().zip(server.stages()) {
+                match returnValue.iter_mut().find(|(t, _)| *t == term) {
+                    Some((_, measurement)) => measurement.add(sk, &bit, value),
+                    None => {
+                        let mut measurement = Self::new();
+                        measurement.add(sk, &bit, value);
+                        returnValue.push((term, measurement));
+                    }
+                }
+            }
+        }
+        returnValue
+    }
+
+    /// Adds the phase errors of `bit`, which encrypts `value`.
+    pub fn add(
+        &mut self,
+        sk: &GlweSecretKey<BinaryKeyKind, Vec<Torus>>,
+        bit: &EncryptedBit,
+        value: bool,
+    ) {
+        for error in phase_errors(sk, bit, value) {
+            self.add_error(error);
+        }
+    }
+
+    /// Adds a phase error, on the torus `[-1/2, 1/2)`.
+    pub fn add_error(&mut self, error: f64) {
+        self.count += 1;
+        self.sum += error;
+        self.sum_squares += error * error;
+        self.max = self.max.max(error.abs());
+    }
+
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    pub fn mean(&self) -> f64 {
+        self.sum / self.count as f64
+    }
+
+    pub fn variance(&self) -> f64 {
+        self.sum_squares / self.count as f64 - self.mean() * self.mean()
+    }
+
+    /// Largest absolute phase error.
+    pub fn max(&self) -> f64 {
+        self.max
+    }
+
+    /// Gaussian noise of the same second moment as the measured errors, which extrapolates
+    /// their tail far beyond the number of samples.
+    pub fn estimate(&self) -> NoiseEstimate {
+        NoiseEstimate::new(self.sum_squares / self.count as f64)
+    }
+
+    /// Probability that a bit decodes wrong, extrapolated by `estimate`.
+    pub fn tail_probability(&self) -> f64 {
+        self.estimate().failure_probability()
+    }
+}
+
+/// Phase error of each coefficient of `bit`, which encrypts `value`, on the torus `[-1/2, 1/2)`.
+/// The bit decodes wrong when the error of the first coefficient reaches 1/4.
+pub(crate) fn phase_errors(
+    sk: &GlweSecretKey<BinaryKeyKind, Vec<Torus>>,
+    bit: &EncryptedBit,
+    value: bool,
+) -> Vec<f64> {
+    let mut phase = PlaintextList::allocate(0, PlaintextCount(sk.polynomial_size().0));
+    sk.decrypt_glwe(&mut phase, bit.as_glwe());
+    phase
+        .as_tensor()
+        .as_slice()
+        .iter()
+        .enumerate()
+        .map(|(i, &p)| {
+            let expected: Torus = if i == 0 && value { 1 << 63 } else { 0 };
+            p.wrapping_sub(expected) as i64 as f64 / 2_f64.powi(64)
+        })
+        .collect()
+}
+
+impl fmt::Display for NoiseMeasurement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} samples, mean {:+.2e}, std dev 2^{:.1}, max 2^{:.1}, failure probability 2^{:.1}",
+            self.count,
+            self.mean(),
+            self.variance().sqrt().log2(),
+            self.max.log2(),
+            self.estimate().log2_failure_probability()
+        )
+    }
+}
@@ -0,0 +1,383 @@
+//! Noise growth of the homomorphic evaluation of the filters. Variances are given on the torus
+//! `[-1/2, 1/2)`, and the noises of distinct ciphertexts are assumed independent.
+
+mod lattice;
+mod measure;
+mod tuner;
+
+use std::fmt;
+
+use crate::{filter::Filter, ParameterSet, SimulatedKeyBit};
+use concrete_core::math::random::RandomGenerator;
+use serde::{Deserialize, Serialize};
+
+pub use measure::NoiseMeasurement;
+
+pub(crate) use lattice::primal_usvp;
+pub(crate) use measure::phase_errors;
+pub(crate) use tuner::{tune, NOISE_MARGIN};
+
+/// Variance of the noise added by each operation of `EncryptedKeyBit` and `EncryptedBit`.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct NoiseModel {
+    glwe_dimension: usize,
+    polynomial_size: usize,
+    base_log: usize,
+    level_count: usize,
+    variance: f64,
+}
+
+impl NoiseModel {
+    /// Model of GGSW key bits encrypted with a noise of standard deviation `std_dev`.
+    pub fn new(
+        glwe_dimension: usize,
+        polynomial_size: usize,
+        base_log: usize,
+        level_count: usize,
+        std_dev: f64,
+    ) -> Self {
+        Self {
+            glwe_dimension,
+            polynomial_size,
+            base_log,
+            level_count,
+            variance: std_dev * std_dev,
+        }
+    }
+
+    pub fn from_parameter_set(set: &ParameterSet) -> Self {
+        Self::new(
+            set.glwe_dimension,
+            set.polynomial_size,
+            set.base_log,
+            set.level_count,
+            set.std_dev,
+        )
+    }
+
+    /// Variance of a freshly encrypted key bit, which `not_inplace` leaves unchanged.
+    pub fn fresh(&self) -> f64 {
+        self.variance
+    }
+
+    /// `as_bit` scales the row of the first level, which encrypts `m / B`, by `B / 2`.
+    pub fn as_bit(&self, key: f64) -> f64 {
+        let half_base = (self.base_log as f64 - 1.).exp2();
+        key * half_base * half_base
+    }
+
+    /// Noise added by an external product with a key bit of variance `key`, whatever the
+    /// value of the key bit.
+    pub fn external_product_noise(&self, key: f64) -> f64 {
+        let (k, n, l) = (
+            self.glwe_dimension as f64,
+            self.polynomial_size as f64,
+            self.level_count as f64,
+        );
+        let base = (self.base_log as f64).exp2();
+        let digits = (base * base + 2.) / 12.;
+        // Each of the `(k + 1) l` decomposed polynomials, with digits in `[-B/2, B/2)`,
+        // multiplies a row of the GGSW.
+        let ggsw = (k + 1.) * l * n * digits * key;
+        // Heuristic for the products computed with 53 bits floating point FFTs.
+        let fft = (-106_f64).exp2() * (k + 1.) * l * n * n * digits / 12.;
+        ggsw + fft
+    }
+
+    /// The decomposition rounds the input to `B^-l`, and the rounding error of the mask is
+    /// multiplied by the binary secret key. It only remains when the key bit is one.
+    pub fn rounding_noise(&self) -> f64 {
+        let (k, n) = (self.glwe_dimension as f64, self.polynomial_size as f64);
+        let precision = ((self.base_log * self.level_count) as f64).exp2();
+        (1. + k * n / 2.) / (12. * precision * precision)
+    }
+
+    /// External product `key & input`, for a key bit of variance `key` and value `value`: the
+    /// noise of the input only remains when the key bit is one.
+    pub fn external_product(&self, key: f64, value: bool, input: f64) -> f64 {
+        let kept = if value {
+            input + self.rounding_noise()
+        } else {
+            0.
+        };
+        kept + self.external_product_noise(key)
+    }
+
+    /// `o0 + key & (o1 - o0)` holds the noise of the selected input and of an external product.
+    pub fn cmux(&self, key: f64, value: bool, o1: f64, o0: f64) -> f64 {
+        if value {
+            o1 + self.rounding_noise() + self.external_product_noise(key)
+        } else {
+            o0 + self.external_product_noise(key)
+        }
+    }
+
+    /// Xor is an addition of the ciphertexts, and negation leaves the noise unchanged.
+    pub fn xor(&self, a: f64, b: f64) -> f64 {
+        a + b
+    }
+
+    /// Noise of a keystream bit evaluated by `filter` over `n` key bits. The noise depends on
+    /// the values of the key bits, through the external products and CMUX: the estimate is the
+    /// largest one over the all zero and all one inputs and a fixed sample of random inputs.
+    pub fn filter(&self, filter: &Filter, n: usize) -> NoiseEstimate {
+        let mut generator = RandomGenerator::new(Some(0));
+        let mut inputs = vec![vec![false; n], vec![true; n]];
+        inputs.extend((0..NOISE_SAMPLES).map(|_| {
+            (0..n)
+                .map(|_| generator.random_uniform_binary::<u8>() == 1)
+                .collect()
+        }));
+        let variance = inputs
+            .iter()
+            .map(|input| {
+                let key = input
+                    .iter()
+                    .map(|&value| SimulatedKeyBit::new(*self, value))
+                    .collect::<Vec<_>>();
+                filter.call(&key).variance()
+            })
+            .fold(0., f64::max);
+        NoiseEstimate::new(variance)
+    }
+}
+
+/// Number of random inputs over which the noise of a filter is estimated.
+const NOISE_SAMPLES: usize = 256;
+
+/// Noise of the transciphered bits, encoded as `m / 2` and decoded wrong once the noise
+/// reaches `1/4`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct NoiseEstimate {
+    variance: f64,
+}
+
+impl NoiseEstimate {
+    pub fn new(variance: f64) -> Self {
+        Self { variance }
+    }
+
+    pub fn variance(&self) -> f64 {
+        self.variance
+    }
+
+    pub fn std_dev(&self) -> f64 {
+        self.variance.sqrt()
+    }
+
+    /// Probability that a transciphered bit decodes wrong, for a Gaussian noise.
+    pub fn failure_probability(&self) -> f64 {
+        self.log2_failure_probability().exp2()
+    }
+
+    /// `log2` of the failure probability, which remains meaningful well below `f64::MIN`.
+    pub fn log2_failure_probability(&self) -> f64 {
+        ln_erfc(0.25 / (2. * self.variance).sqrt()) / std::f64::consts::LN_2
+    }
+}
+
+impl fmt::Display for NoiseEstimate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "noise std dev 2^{:.1}, failure probability 2^{:.1}",
+            self.std_dev().log2(),
+            self.log2_failure_probability()
+        )
+    }
+}
+
+/// `ln erfc(x)` for `x >= 0`, with the Chebyshev fit of Numerical Recipes, of relative error
+/// below `1.2e-7` on `erfc`.
+fn ln_erfc(x: f64) -> f64 {
+    let t = 1. / (1. + 0.5 * x);
+    let poly = [
+        -1.265_512_23,
+        1.000_023_68,
+        0.374_091_96,
+        0.096_784_18,
+        -0.186_288_06,
+        0.278_868_07,
+        -1.135_203_98,
+        1.488_515_87,
+        -0.822_152_23,
+        0.170_872_77,
+    ]
+    .iter()
+    .rev()
+    .fold(0., |acc, c| c + t * acc);
+    t.ln() - x * x + poly
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        filter::FilterType, multiplexer::Multiplexer, EncryptedBit, EncryptedKeyBit, Encrypter,
+        SimulatedBit, SystemParameters,
+    };
+    use concrete_commons::{
+        dispersion::StandardDev,
+        parameters::{
+            DecompositionBaseLog, DecompositionLevelCount, GlweDimension, PolynomialSize,
+        },
+    };
+    use concrete_core::crypto::secret::{generators::SecretRandomGenerator, GlweSecretKey};
+    use strum::IntoEnumIterator;
+
+    #[test]
+    fn erfc() {
+        // erfc(1) and erfc(3).
+        assert!((ln_erfc(1.).exp() - 0.157_299_207).abs() < 1e-7);
+        assert!((ln_erfc(3.).exp() / 2.209_049_7e-5 - 1.).abs() < 1e-6);
+        // A noise of standard deviation 1/8 reaches 1/4 about 4.55% of the time.
+        let estimate = NoiseEstimate::new(1. / 64.);
+        assert!((estimate.failure_probability() - 0.045_500_26).abs() < 1e-6);
+        assert!(NoiseEstimate::new(1e-12).log2_failure_probability() < -1e9);
+    }
+
+    #[test]
+    fn lattice() {
+        // Core-SVP cost of LWE in dimension 630 with a noise of 2^-15, as in the original TFHE
+        // bootstrapping keys, and monotony in the dimension and the noise.
+        let security = lattice::primal_usvp(630, 2_f64.powi(-15));
+        assert!(security > 90. && security < 110., "{}", security);
+        assert!(lattice::primal_usvp(1024, 2_f64.powi(-25)) > security);
+        assert!(lattice::primal_usvp(630, 2_f64.powi(-20)) < security);
+    }
+
+    #[test]
+    fn parameter_sets() {
+        for params in SystemParameters::iter() {
+            let set = params.parameter_set();
+            let estimate = NoiseModel::from_parameter_set(&set).filter(&set.filter, set.n);
+            assert!(
+                estimate.log2_failure_probability() < -128.,
+                "{}: {}",
+                set.name,
+                estimate
+            );
+        }
+        // Keys of any density stay within the margin the tuner leaves over the estimate.
+        let mut generator = RandomGenerator::new(Some(1));
+        for params in SystemParameters::iter() {
+            let set = params.parameter_set();
+            let model = NoiseModel::from_parameter_set(&set);
+            let estimate = model.filter(&set.filter, set.n).variance();
+            for density in 1..8_u32 {
+                let key = (0..set.n)
+                    .map(|_| {
+                        SimulatedKeyBit::new(
+                            model,
+                            generator.random_uniform_n_lsb::<u32>(3) < density,
+                        )
+                    })
+                    .collect::<Vec<_>>();
+                assert!(set.filter.call(&key).variance() <= NOISE_MARGIN * estimate);
+            }
+        }
+        // The deeper the circuit, the larger the noise.
+        let model = NoiseModel::new(1, 1024, 5, 6, 1e-9);
+        let noise = |filter: Filter, n| model.filter(&filter, n).variance();
+        assert!(
+            noise(Filter::new(FilterType::DSM, &[1, 0, 4]), 13)
+                < noise(Filter::new(FilterType::DSM, &[1, 0, 0, 3]), 13)
+        );
+        assert!(
+            noise(Filter::new(FilterType::XorThr, &[4, 3]), 9)
+                < noise(Filter::new(FilterType::XorThr, &[4, 5]), 13)
+        );
+    }
+
+    #[test]
+    fn simulation() {
+        for params in SystemParameters::iter() {
+            let set = params.parameter_set();
+            let key = (0..set.key_size).map(|i| i % 3 == 0).collect::<Vec<_>>();
+            let model = NoiseModel::from_parameter_set(&set);
+            let mut clear = Encrypter::from_parameter_set(key.clone(), &set, 0, false).unwrap();
+            let mut simulated = Encrypter::from_parameter_set(
+                SimulatedKeyBit::encrypt_key(&key, model),
+                &set,
+                0,
+                false,
+            )
+            .unwrap();
+
+            let message = (0..256).map(|i| i % 5 < 2).collect::<Vec<_>>();
+            let mut ciphertext = vec![false; message.len()];
+            let mut transciphered = vec![SimulatedBit::new(false, 0.); message.len()];
+            clear.encrypt(&mut ciphertext, &message);
+            simulated.decrypt(&mut transciphered, &ciphertext);
+            let estimate = set.noise_estimate();
+            for (bit, &m) in transciphered.iter().zip(&message) {
+                assert_eq!(bit.value(), m);
+                assert!(bit.variance() > 0. && bit.variance() <= estimate.variance());
+            }
+        }
+    }
+
+    #[test]
+    fn empirical() {
+        let (k, n, base_log, level, std_dev) = (1, 512, 5, 6, 2_f64.powi(-30));
+        let mut generator = SecretRandomGenerator::new(None);
+        let sk =
+            GlweSecretKey::generate_binary(GlweDimension(k), PolynomialSize(n), &mut generator);
+        let model = NoiseModel::new(k, n, base_log, level, std_dev);
+        let key = EncryptedKeyBit::encrypt_key(
+            &[true; 40],
+            &sk,
+            DecompositionLevelCount(level),
+            DecompositionBaseLog(base_log),
+            StandardDev::from_standard_dev(std_dev),
+        );
+        let measured = |bits: &[EncryptedBit], value: bool| {
+            NoiseMeasurement::from_bits(&sk, bits, &vec![value; bits.len()])
+                .estimate()
+                .variance()
+        };
+        let close = |measured: f64, predicted: f64| {
+            assert!(
+                measured < 2. * predicted && measured > predicted / 4.,
+                "measured 2^{:.2}, predicted 2^{:.2}",
+                measured.log2(),
+                predicted.log2()
+            )
+        };
+
+        // Monomials of degree 4, made of `as_bit` and three external products.
+        let monomials = key
+            .chunks(4)
+            .map(|x| {
+                let mut temp = x[0].as_bit();
+                for x_j in &x[1..] {
+                    temp &= x_j.clone();
+                }
+                temp
+            })
+            .collect::<Vec<_>>();
+        let mut predicted = model.as_bit(model.fresh());
+        for _ in 1..4 {
+            predicted = model.external_product(model.fresh(), true, predicted);
+        }
+        close(measured(&monomials, true), predicted);
+
+        // CMUX between the negation of a monomial and a fresh bit.
+        let selected = key
+            .iter()
+            .zip(&monomials)
+            .map(|(x, monomial)| x.mux(&!monomial.clone(), &x.as_bit()))
+            .collect::<Vec<_>>();
+        close(
+            measured(&selected, false),
+            model.cmux(model.fresh(), true, predicted, model.as_bit(model.fresh())),
+        );
+
+        // Xor of two monomials.
+        let xored = monomials
+            .chunks(2)
+            .map(|pair| pair[0].clone() ^ pair[1].clone())
+            .collect::<Vec<_>>();
+        close(measured(&xored, false), model.xor(predicted, predicted));
+    }
+}
@@ -0,0 +1,118 @@
+//! Search of the cheapest FHE parameters reaching a security level and a failure probability.
+
+use super::{lattice, NoiseEstimate, NoiseModel};
+use crate::{ParameterError, ParameterSet, Torus};
+
+/// GLWE dimensions and `log2` of the polynomial sizes the tuner considers.
+const GLWE_DIMENSIONS: [usize; 3] = [1, 2, 3];
+const LOG_POLYNOMIAL_SIZES: std::ops::RangeInclusive<u32> = 8..=13;
+
+/// The noise estimate of a filter is the largest one over a sample of keys, not a bound over
+/// every key. The tuner asks for the failure probability with this many times the estimated
+/// variance, which leaves room for keys noisier than the sampled ones. This margin is a
+/// heuristic, not derived from a worst case: for the presets and the toy sets, random keys of
+/// any density stay below the estimate, which the all one key reaches, but no key is proven to
+/// stay below twice the estimate.
+pub(crate) const NOISE_MARGIN: f64 = 2.;
+
+/// Smallest noise on the torus worth considering: below it, the rounding to 64 bits and the
+/// FFT errors dominate anyway.
+const MIN_LOG_STD_DEV: f64 = -60.;
+
+/// Smallest standard deviation, up to `2^-8` of its `log2`, for which the GLWE problem in
+/// dimension `n` reaches `security` bits.
+fn min_std_dev(n: usize, security: f64) -> Option<f64> {
+    let secure = |log_std_dev: f64| lattice::primal_usvp(n, log_std_dev.exp2()) >= security;
+    let (mut low, mut high) = (MIN_LOG_STD_DEV, -2.);
+    if secure(low) {
+        return Some(low.exp2());
+    }
+    if !secure(high) {
+        return None;
+    }
+    while high - low > 1. / 256. {
+        let mid = (low + high) / 2.;
+        if secure(mid) {
+            high = mid;
+        } else {
+            low = mid;
+        }
+    }
+    Some(high.exp2())
+}
+
+/// Copy of `set` with the cheapest FHE parameters whose keys reach `security` bits and whose
+/// transciphered bits decode wrong with an estimated probability of at most `2^log2_failure`,
+/// a sampled estimate with the heuristic `NOISE_MARGIN` rather than a bound.
+///
+/// For each GLWE dimension and polynomial size, the noise is the smallest one that keeps the
+/// GLWE problem hard enough, and for each number of levels, the base is the one minimizing the
+/// noise of a single external product. The candidates are then tried from the cheapest, in
+/// `(k + 1)^2 l N log N` operations per external product, against the noise of the filter
+/// with a margin of `NOISE_MARGIN`.
+pub(crate) fn tune(
+    set: &ParameterSet,
+    security: f64,
+    log2_failure: f64,
+) -> Result<ParameterSet, ParameterError> {
+    set.filter.validate(set.n)?;
+    let mut candidates = Vec::new();
+    for &glwe_dimension in &GLWE_DIMENSIONS {
+        for log_size in LOG_POLYNOMIAL_SIZES {
+            let polynomial_size = 1 << log_size;
+            let std_dev = match min_std_dev(glwe_dimension * polynomial_size, security) {
+                Some(std_dev) => std_dev,
+                None => continue,
+            };
+            for level_count in 1..=Torus::BITS as usize {
+                let base_log = (1..=Torus::BITS as usize / level_count)
+                    .map(|base_log| {
+                        let model = NoiseModel::new(
+                            glwe_dimension,
+                            polynomial_size,
+                            base_log,
+                            level_count,
+                            std_dev,
+                        );
+                        let noise =
+                            model.external_product_noise(model.fresh()) + model.rounding_noise();
+                        (base_log, noise)
+                    })
+                    .min_by(|a, b| a.1.total_cmp(&b.1))
+                    .unwrap()
+                    .0;
+                let cost =
+                    (glwe_dimension + 1).pow(2) * level_count * polynomial_size * log_size as usize;
+                candidates.push((
+                    cost,
+                    ParameterSet {
+                        glwe_dimension,
+                        polynomial_size,
+                        base_log,
+                        level_count,
+                        std_dev,
+                        ..set.clone()
+                    },
+                ));
+            }
+        }
+    }
+    candidates.sort_by_key(|(cost, _)| *cost);
+    candidates
+        .into_iter()
+        .map(|(_, candidate)| candidate)
+        .find(|candidate| {
+            let variance = NOISE_MARGIN * candidate.noise_estimate().variance();
+            NoiseEstimate::new(variance).log2_failure_probability() <= log2_failure
+        })
+        .ok_or_else(|| {
+            ParameterError::Fhe(format!(
+                "no GLWE dimension up to {} and polynomial size up to {} reach {} bits of \
+                 security with a failure probability of 2^{}",
+                GLWE_DIMENSIONS[GLWE_DIMENSIONS.len() - 1],
+                1 << LOG_POLYNOMIAL_SIZES.end(),
+                security,
+                log2_failure
+            ))
+        })
+}
@@ -0,0 +1,27 @@
+use aes::{
+    cipher::{NewCipher, StreamCipher},
+    Aes128,
+};
+use ctr::Ctr128BE;
+
+use crate::{prng::KeystreamPrng, Nonce};
+
+pub struct AesCtrPrng(Ctr128BE<Aes128>);
+
+impl AesCtrPrng {
+    /// The nonce is the AES key, and the counter starts at zero.
+    pub fn new(nonce: Nonce) -> Self {
+        Self(Ctr128BE::new(&nonce.to_be_bytes().into(), &[0; 16].into()))
+    }
+}
+
+impl KeystreamPrng for AesCtrPrng {
+    fn fill_bytes(&mut self, bytes: &mut [u8]) {
+        bytes.iter_mut().for_each(|b| *b = 0);
+        self.0.apply_keystream(bytes);
+    }
+
+    fn reset(&mut self, nonce: Nonce) {
+        *self = Self::new(nonce);
+    }
+}
@@ -0,0 +1,29 @@
+use chacha20::{
+    cipher::{NewCipher, StreamCipher},
+    ChaCha20,
+};
+
+use crate::{prng::KeystreamPrng, Nonce};
+
+pub struct ChaCha20Prng(ChaCha20);
+
+impl ChaCha20Prng {
+    /// The nonce fills the first half of the ChaCha20 key, the rest of the key and the
+    /// ChaCha20 nonce being zero.
+    pub fn new(nonce: Nonce) -> Self {
+        let mut key = [0; 32];
+        key[..16].copy_from_slice(&nonce.to_be_bytes());
+        Self(ChaCha20::new(&key.into(), &[0; 12].into()))
+    }
+}
+
+impl KeystreamPrng for ChaCha20Prng {
+    fn fill_bytes(&mut self, bytes: &mut [u8]) {
+        bytes.iter_mut().for_each(|b| *b = 0);
+        self.0.apply_keystream(bytes);
+    }
+
+    fn reset(&mut self, nonce: Nonce) {
+        *self = Self::new(nonce);
+    }
+}
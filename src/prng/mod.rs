@@ -0,0 +1,146 @@
+mod aes_ctr;
+mod chacha;
+mod reference;
+mod shake;
+
+use crate::Nonce;
+pub use aes_ctr::AesCtrPrng;
+pub use chacha::ChaCha20Prng;
+pub use reference::ReferencePrng;
+use serde::{Deserialize, Serialize};
+pub use shake::Shake128Prng;
+
+/// Source of the public randomness driving subset selection and whitening. Implementations
+/// only depend on the nonce, so that the clear and homomorphic sides draw the same bits. Besides
+/// the generators of `PrngType`, any implementation can be given to `Encrypter::with_generator`.
+pub trait KeystreamPrng: Send {
+    /// Fills `bytes` with the next output bytes of the generator.
+    fn fill_bytes(&mut self, bytes: &mut [u8]);
+
+    /// Restarts the generator from its first output under `nonce`, when the keystream is
+    /// restarted or moved backwards.
+    fn reset(&mut self, nonce: Nonce);
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PrngType {
+    /// AES-128 in counter mode, keyed by the nonce, as in the FiLIP specification.
+    AesCtr,
+    Shake128,
+    ChaCha20,
+    /// The generator and subset sampler of the reference implementation of the transciphering
+    /// paper, to reproduce its keystream. Its sampler only reaches the first
+    /// `2^floor(log2(key_size - i))` candidates at each position `i`, so it is not uniform.
+    Reference,
+}
+
+impl PrngType {
+    pub fn generator(self, nonce: Nonce) -> Box<dyn KeystreamPrng + Send> {
+        match self {
+            Self::AesCtr => Box::new(AesCtrPrng::new(nonce)),
+            Self::Shake128 => Box::new(Shake128Prng::new(nonce)),
+            Self::ChaCha20 => Box::new(ChaCha20Prng::new(nonce)),
+            Self::Reference => Box::new(ReferencePrng::new(nonce)),
+        }
+    }
+}
+
+const BUFFER_SIZE: usize = 64;
+
+/// Reads the output of a `KeystreamPrng` bit by bit, most significant bit of each byte first.
+pub(crate) struct BitGenerator {
+    prng: Box<dyn KeystreamPrng + Send>,
+    buffer: [u8; BUFFER_SIZE],
+    position: usize,
+}
+
+impl BitGenerator {
+    pub(crate) fn new(prng: Box<dyn KeystreamPrng + Send>) -> Self {
+        Self {
+            prng,
+            buffer: [0; BUFFER_SIZE],
+            position: 8 * BUFFER_SIZE,
+        }
+    }
+
+    /// Restarts the generator under `nonce`, dropping the buffered bits.
+    pub(crate) fn reset(&mut self, nonce: Nonce) {
+        self.prng.reset(nonce);
+        self.position = 8 * BUFFER_SIZE;
+    }
+
+    pub(crate) fn random_bit(&mut self) -> bool {
+        if self.position == 8 * BUFFER_SIZE {
+            self.prng.fill_bytes(&mut self.buffer);
+            self.position = 0;
+        }
+        let bit = self.buffer[self.position / 8] & (0x80 >> (self.position % 8)) != 0;
+        self.position += 1;
+        bit
+    }
+
+    /// Draws `n` bits, the first one drawn being the most significant.
+    pub(crate) fn random_bits(&mut self, n: usize) -> u64 {
+        assert!(n <= 64, "cannot draw more than 64 bits at once");
+        (0..n).fold(0, |acc, _| (acc << 1) | self.random_bit() as u64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn first_bytes(prng: PrngType, nonce: Nonce) -> Vec<u8> {
+        let mut bytes = vec![0; 16];
+        prng.generator(nonce).fill_bytes(&mut bytes);
+        bytes
+    }
+
+    #[test]
+    fn aes_ctr() {
+        // AES-128 of the all zero block under the all zero key.
+        assert_eq!(
+            first_bytes(PrngType::AesCtr, 0),
+            vec![
+                0x66, 0xe9, 0x4b, 0xd4, 0xef, 0x8a, 0x2c, 0x3b, 0x88, 0x4c, 0xfa, 0x59, 0xca, 0x34,
+                0x2b, 0x2e
+            ]
+        );
+    }
+
+    #[test]
+    fn shake128() {
+        // SHAKE-128 of sixteen zero bytes.
+        assert_eq!(
+            first_bytes(PrngType::Shake128, 0),
+            vec![
+                0x8f, 0x8e, 0x4f, 0x61, 0x2e, 0x61, 0xff, 0xb9, 0xd7, 0x8c, 0x3e, 0xa7, 0x07, 0xe3,
+                0x77, 0x68
+            ]
+        );
+    }
+
+    #[test]
+    fn chacha20() {
+        // First ChaCha20 block under the all zero key and nonce (RFC 7539, A.1).
+        assert_eq!(
+            first_bytes(PrngType::ChaCha20, 0),
+            vec![
+                0x76, 0xb8, 0xe0, 0xad, 0xa0, 0xf1, 0x3d, 0x90, 0x40, 0x5d, 0x6a, 0xe5, 0x53, 0x86,
+                0xbd, 0x28
+            ]
+        );
+    }
+
+    #[test]
+    fn bits_follow_bytes() {
+        for prng in [PrngType::AesCtr, PrngType::Shake128, PrngType::ChaCha20] {
+            let mut bytes = vec![0; 2 * BUFFER_SIZE];
+            prng.generator(42).fill_bytes(&mut bytes);
+            let mut bits = BitGenerator::new(prng.generator(42));
+            for byte in bytes {
+                assert_eq!(bits.random_bits(8), byte as u64);
+            }
+        }
+    }
+}
@@ -0,0 +1,31 @@
+use concrete_core::math::random::RandomGenerator;
+
+use crate::{prng::KeystreamPrng, Nonce};
+
+/// The generator of the reference implementation of the transciphering paper: the CSPRNG of
+/// concrete, seeded with the nonce. As a `KeystreamPrng`, it only gives the random bytes; the
+/// subsets and whitening of the reference implementation are drawn from it with its own sampler,
+/// see `PrngType::Reference`.
+pub struct ReferencePrng(RandomGenerator);
+
+impl ReferencePrng {
+    pub fn new(nonce: Nonce) -> Self {
+        Self(RandomGenerator::new(Some(nonce)))
+    }
+
+    pub(crate) fn generator(&mut self) -> &mut RandomGenerator {
+        &mut self.0
+    }
+}
+
+impl KeystreamPrng for ReferencePrng {
+    fn fill_bytes(&mut self, bytes: &mut [u8]) {
+        bytes
+            .iter_mut()
+            .for_each(|b| *b = self.0.random_uniform::<u8>());
+    }
+
+    fn reset(&mut self, nonce: Nonce) {
+        *self = Self::new(nonce);
+    }
+}
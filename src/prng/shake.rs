@@ -0,0 +1,27 @@
+use sha3::{
+    digest::{ExtendableOutput, Update, XofReader},
+    Sha3XofReader, Shake128,
+};
+
+use crate::{prng::KeystreamPrng, Nonce};
+
+pub struct Shake128Prng(Sha3XofReader);
+
+impl Shake128Prng {
+    /// Squeezes SHAKE-128 after absorbing the nonce.
+    pub fn new(nonce: Nonce) -> Self {
+        let mut shake = Shake128::default();
+        shake.update(nonce.to_be_bytes());
+        Self(shake.finalize_xof())
+    }
+}
+
+impl KeystreamPrng for Shake128Prng {
+    fn fill_bytes(&mut self, bytes: &mut [u8]) {
+        self.0.read(bytes);
+    }
+
+    fn reset(&mut self, nonce: Nonce) {
+        *self = Self::new(nonce);
+    }
+}
@@ -1,45 +1,180 @@
-use crate::Multiplexer;
-use concrete_core::math::random::RandomGenerator;
+use crate::{
+    prng::{BitGenerator, KeystreamPrng, PrngType, ReferencePrng},
+    Multiplexer,
+};
 use std::cell::{Ref, RefCell};
 
+/// Public initialisation vector of a keystream. Two encrypters sharing the same key and nonce
+/// produce the same keystream, while distinct nonces give independent keystreams.
+pub type Nonce = u128;
+
 pub struct SymmetricKey<M: Multiplexer> {
     key: Vec<M>,
+    nonce: Nonce,
+    position: u64,
     indices: RefCell<Vec<usize>>,
     key_round: RefCell<Vec<M>>,
     whitening: Vec<bool>,
-    rng: RandomGenerator,
+    rng: Sampler,
+}
+
+/// Draws the key indices and whitening bits of the subsets.
+enum Sampler {
+    /// Indices drawn uniformly from the bits of a generator, see `gen_range`.
+    Uniform(BitGenerator),
+    /// Indices and whitening bits drawn as in the reference implementation, see
+    /// `reference_range`.
+    Reference(ReferencePrng),
+}
+
+impl Sampler {
+    fn new(prng: PrngType, nonce: Nonce) -> Self {
+        match prng {
+            PrngType::Reference => Self::Reference(ReferencePrng::new(nonce)),
+            prng => Self::Uniform(BitGenerator::new(prng.generator(nonce))),
+        }
+    }
+
+    fn reset(&mut self, nonce: Nonce) {
+        match self {
+            Self::Uniform(rng) => rng.reset(nonce),
+            Self::Reference(prng) => prng.reset(nonce),
+        }
+    }
+
+    fn index(&mut self, min: usize, max: usize) -> usize {
+        match self {
+            Self::Uniform(rng) => gen_range(rng, min, max),
+            Self::Reference(prng) => reference_range(prng, min, max),
+        }
+    }
+
+    fn whitening_bit(&mut self) -> bool {
+        match self {
+            Self::Uniform(rng) => rng.random_bit(),
+            Self::Reference(prng) => prng.generator().random_uniform_binary::<u8>() == 1,
+        }
+    }
 }
 
 impl<M: Multiplexer> SymmetricKey<M> {
-    pub fn new(key: Vec<M>, n: usize, seed: u128) -> Self {
+    pub fn new(key: Vec<M>, n: usize, prng: PrngType, nonce: Nonce) -> Self {
+        Self::with_sampler(key, n, Sampler::new(prng, nonce), nonce)
+    }
+
+    /// Symmetric key whose subsets and whitening are drawn from `generator`, restarted under
+    /// `nonce`.
+    pub fn with_generator(
+        key: Vec<M>,
+        n: usize,
+        mut generator: Box<dyn KeystreamPrng + Send>,
+        nonce: Nonce,
+    ) -> Self {
+        generator.reset(nonce);
+        Self::with_sampler(
+            key,
+            n,
+            Sampler::Uniform(BitGenerator::new(generator)),
+            nonce,
+        )
+    }
+
+    fn with_sampler(key: Vec<M>, n: usize, rng: Sampler, nonce: Nonce) -> Self {
         let mut indices = Vec::with_capacity(key.len() as usize);
         indices.extend(0..key.len());
         let key_round = key.iter().take(n).cloned().collect::<Vec<M>>();
         let whitening = vec![false; n];
         Self {
             key,
+            nonce,
+            position: 0,
             indices: RefCell::new(indices),
             key_round: RefCell::new(key_round),
             whitening,
-            rng: RandomGenerator::new(Some(seed)),
+            rng,
         }
     }
 
-    pub fn random_whitened_subset(&mut self) -> Ref<'_, Vec<M>> {
+    pub fn key(&self) -> &[M] {
+        &self.key
+    }
+
+    pub fn nonce(&self) -> Nonce {
+        self.nonce
+    }
+
+    /// Restarts the keystream from its first bit under a new nonce.
+    pub fn set_nonce(&mut self, nonce: Nonce) {
+        let mut indices = self.indices.borrow_mut();
+        indices.clear();
+        indices.extend(0..self.key.len());
+        drop(indices);
+        self.nonce = nonce;
+        self.position = 0;
+        self.rng.reset(nonce);
+    }
+
+    /// Switches the generator of the subset selection and whitening, restarting the keystream.
+    pub fn set_prng(&mut self, prng: PrngType) {
+        self.rng = Sampler::new(prng, self.nonce);
+        self.set_nonce(self.nonce);
+    }
+
+    /// Switches to a custom generator, restarting the keystream.
+    pub fn set_generator(&mut self, generator: Box<dyn KeystreamPrng + Send>) {
+        self.rng = Sampler::Uniform(BitGenerator::new(generator));
+        self.set_nonce(self.nonce);
+    }
+
+    /// Index of the next keystream bit.
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    /// Moves the keystream to bit `position`, replaying the PRNG from the nonce if the position
+    /// lies behind the current one. No subset is ever materialised.
+    pub fn seek(&mut self, position: u64) {
+        if position < self.position {
+            self.set_nonce(self.nonce);
+        }
+        self.skip(position - self.position);
+    }
+
+    /// Discards the next `n` keystream bits, only advancing the index swaps and whitening.
+    pub fn skip(&mut self, n: u64) {
+        for _ in 0..n {
+            self.advance();
+        }
+    }
+
+    fn advance(&mut self) {
         let mut indices = self.indices.borrow_mut();
-        let mut key_round = self.key_round.borrow_mut();
         let n = self.whitening.len();
         let key_len = self.key.len();
 
         for i in 0..n {
-            let j = gen_range(&mut self.rng, i, key_len);
+            let j = self.rng.index(i, key_len);
             indices.swap(i, j);
         }
 
         for w in self.whitening.iter_mut() {
-            *w = self.rng.random_uniform_binary::<u8>() == 1;
+            *w = self.rng.whitening_bit();
         }
+        self.position += 1;
+    }
+
+    /// Key indices and whitening bits of the last keystream bit.
+    pub fn subset(&self) -> (Vec<usize>, Vec<bool>) {
+        let n = self.whitening.len();
+        (self.indices.borrow()[..n].to_vec(), self.whitening.clone())
+    }
+
+    pub fn random_whitened_subset(&mut self) -> Ref<'_, Vec<M>> {
+        self.advance();
 
+        let indices = self.indices.borrow();
+        let n = self.whitening.len();
+        let mut key_round = self.key_round.borrow_mut();
         key_round
             .iter_mut()
             .take(n)
@@ -51,19 +186,163 @@ impl<M: Multiplexer> SymmetricKey<M> {
                 }
             });
         drop(key_round);
+        drop(indices);
 
         self.key_round.borrow()
     }
 }
 
-fn gen_range(rng: &mut RandomGenerator, min: usize, max: usize) -> usize {
-    if min > max {
-        panic!("`min` of range must be less than or equal to `max`");
+/// Draws uniformly in `[min, max)`. A 128 bits draw is scaled to the range with a widening
+/// multiplication: every index is reachable, the statistical distance to the uniform
+/// distribution is at most `(max - min) / 2^128`, and, unlike rejection sampling, the time taken
+/// does not depend on the drawn value.
+fn gen_range(rng: &mut BitGenerator, min: usize, max: usize) -> usize {
+    if min >= max {
+        panic!("`min` of range must be less than `max`");
+    }
+    let range = (max - min) as u128;
+    let high = u128::from(rng.random_bits(64)) * range;
+    let low = u128::from(rng.random_bits(64)) * range;
+    // floor((high * 2^64 + low) / 2^128), without overflowing.
+    min + ((high + (low >> 64)) >> 64) as usize
+}
+
+/// Draws in `[min, max)` as the reference implementation does: `floor(log2(max - min))` bits
+/// are drawn and added to `min`, so that only the first `2^floor(log2(max - min))` indices of
+/// the range can be drawn. It is only kept to reproduce the reference keystream.
+fn reference_range(prng: &mut ReferencePrng, min: usize, max: usize) -> usize {
+    if min >= max {
+        panic!("`min` of range must be less than `max`");
     }
     let bit_len = ((max - min) as f64).log2().floor() as usize;
+    let rng = prng.generator();
     let mut a = (min as u32 + rng.random_uniform_n_lsb::<u32>(bit_len)) as usize;
     while a >= max {
         a = (min as u32 + rng.random_uniform_n_lsb::<u32>(bit_len)) as usize;
     }
     a
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn subsets(key: &[bool], nonce: Nonce, rounds: usize) -> Vec<Vec<bool>> {
+        let mut key = SymmetricKey::new(key.to_vec(), 16, PrngType::AesCtr, nonce);
+        (0..rounds)
+            .map(|_| key.random_whitened_subset().clone())
+            .collect()
+    }
+
+    #[test]
+    fn same_nonce_same_keystream() {
+        let key = (0..256).map(|i| i % 3 == 0).collect::<Vec<_>>();
+        assert_eq!(subsets(&key, 42, 8), subsets(&key, 42, 8));
+    }
+
+    #[test]
+    fn different_nonces_different_keystreams() {
+        let key = (0..256).map(|i| i % 3 == 0).collect::<Vec<_>>();
+        assert_ne!(subsets(&key, 42, 8), subsets(&key, 43, 8));
+    }
+
+    #[test]
+    fn set_nonce_restarts_keystream() {
+        let key = (0..256).map(|i| i % 3 == 0).collect::<Vec<_>>();
+        let mut symmetric_key = SymmetricKey::new(key.clone(), 16, PrngType::AesCtr, 7);
+        symmetric_key.random_whitened_subset();
+        symmetric_key.set_nonce(42);
+        assert_eq!(symmetric_key.nonce(), 42);
+        assert_eq!(
+            *symmetric_key.random_whitened_subset(),
+            subsets(&key, 42, 1)[0]
+        );
+    }
+
+    #[test]
+    fn gen_range_is_uniform() {
+        let mut rng = BitGenerator::new(PrngType::AesCtr.generator(42));
+        let mut counts = [0_usize; 7];
+        for _ in 0..70_000 {
+            let a = gen_range(&mut rng, 5, 12);
+            assert!((5..12).contains(&a));
+            counts[a - 5] += 1;
+        }
+        // Each count is 10000 on average, with a standard deviation below 100.
+        assert!(counts.iter().all(|&c| (9_500..10_500).contains(&c)));
+    }
+
+    #[test]
+    fn every_index_reachable_at_every_position() {
+        let key_size = 24;
+        let rounds = 24_000;
+        let key = vec![false; key_size];
+        let mut symmetric_key = SymmetricKey::new(key, key_size, PrngType::AesCtr, 42);
+        let mut counts = vec![vec![0_usize; key_size]; key_size];
+        for _ in 0..rounds {
+            symmetric_key.skip(1);
+            for (position, &index) in symmetric_key.indices.borrow().iter().enumerate() {
+                counts[position][index] += 1;
+            }
+        }
+        // Each count is 1000 on average, with a standard deviation close to 31.
+        assert!(counts.iter().flatten().all(|&c| (800..1_200).contains(&c)));
+    }
+
+    /// Counter mode over a trivial block function, standing for a user-supplied generator.
+    struct CounterPrng(u128);
+
+    impl KeystreamPrng for CounterPrng {
+        fn fill_bytes(&mut self, bytes: &mut [u8]) {
+            for byte in bytes {
+                self.0 = self.0.wrapping_mul(0x9e37_79b9_7f4a_7c15).wrapping_add(1);
+                *byte = (self.0 >> 64) as u8;
+            }
+        }
+
+        fn reset(&mut self, nonce: Nonce) {
+            self.0 = nonce;
+        }
+    }
+
+    #[test]
+    fn custom_generator() {
+        fn assert_send<T: Send>() {}
+        assert_send::<SymmetricKey<bool>>();
+
+        let key = (0..256).map(|i| i % 3 == 0).collect::<Vec<_>>();
+        let mut symmetric_key =
+            SymmetricKey::with_generator(key.clone(), 16, Box::new(CounterPrng(0)), 42);
+        let expected = (0..4)
+            .map(|_| symmetric_key.random_whitened_subset().clone())
+            .collect::<Vec<_>>();
+        assert_ne!(expected, subsets(&key, 42, 4));
+
+        // Seeking back restarts the custom generator, which gives the same subsets.
+        symmetric_key.seek(1);
+        assert_eq!(*symmetric_key.random_whitened_subset(), expected[1]);
+        symmetric_key.set_prng(PrngType::AesCtr);
+        assert_eq!(
+            *symmetric_key.random_whitened_subset(),
+            subsets(&key, 42, 1)[0]
+        );
+    }
+
+    #[test]
+    fn seek_matches_sequential_keystream() {
+        let key = (0..256).map(|i| i % 3 == 0).collect::<Vec<_>>();
+        let expected = subsets(&key, 42, 10);
+        let mut symmetric_key = SymmetricKey::new(key, 16, PrngType::AesCtr, 42);
+
+        symmetric_key.skip(7);
+        assert_eq!(symmetric_key.position(), 7);
+        assert_eq!(*symmetric_key.random_whitened_subset(), expected[7]);
+
+        symmetric_key.seek(2);
+        assert_eq!(*symmetric_key.random_whitened_subset(), expected[2]);
+
+        symmetric_key.seek(9);
+        assert_eq!(*symmetric_key.random_whitened_subset(), expected[9]);
+        assert_eq!(symmetric_key.position(), 10);
+    }
+}
@@ -1,6 +1,9 @@
 use concrete_core::math::{random::RandomGenerator, tensor::AsRefSlice};
-use std::env;
-use FiLIP::{Encrypter, SystemParameters, Torus};
+use std::{
+    env,
+    io::{Read, Write},
+};
+use FiLIP::{Encrypter, KeystreamReader, KeystreamWriter, PacketError, SystemParameters, Torus};
 
 fn main() {
     let args: Vec<String> = env::args().collect();
@@ -30,10 +33,13 @@ fn main() {
 }
 
 pub fn clear(parameters: &SystemParameters, n_iter: usize) {
+    let mut generator = RandomGenerator::new(None);
+    let nonce = generator.random_uniform();
+
     let (mut encryptor, mut decryptor) =
-        Encrypter::<bool>::new::<bool>(parameters, None, None, None, None);
+        Encrypter::<bool>::new::<bool>(parameters, nonce, None, None, None, None).unwrap();
+    clear_key(parameters, n_iter);
 
-    let mut generator = RandomGenerator::new(None);
     let message = generator
         .random_uniform_binary_tensor::<Torus>(n_iter)
         .as_slice()
@@ -48,4 +54,99 @@ pub fn clear(parameters: &SystemParameters, n_iter: usize) {
     decryptor.decrypt(&mut decryption, &ciphertext);
 
     assert_eq!(message, decryption);
+
+    // The keystream used by `encrypt` cannot be reused by a packet.
+    encryptor.seek(0);
+    assert!(matches!(
+        encryptor.encrypt_packet(&message[..1]),
+        Err(PacketError::Overlap { .. })
+    ));
+
+    // Packets transciphered in reverse order, with duplicates rejected.
+    encryptor.set_nonce(nonce.wrapping_add(1));
+    decryptor.set_nonce(nonce.wrapping_add(1));
+    let packets = message
+        .chunks(8)
+        .map(|chunk| encryptor.encrypt_packet(chunk).unwrap())
+        .collect::<Vec<_>>();
+
+    let mut decryption = vec![Default::default(); n_iter];
+    let mut duplicate = vec![Default::default(); 8];
+    for packet in packets.iter().rev() {
+        let start = packet.offset() as usize;
+        decryptor
+            .decrypt_packet(&mut decryption[start..start + packet.len()], packet)
+            .unwrap();
+        assert!(decryptor.decrypt_packet(&mut duplicate, packet).is_err());
+    }
+
+    // A buffer too short for the packet is an error, and does not consume the packet.
+    decryptor.set_nonce(nonce.wrapping_add(1));
+    let mut short = vec![Default::default(); packets[0].len() - 1];
+    assert!(decryptor.decrypt_packet(&mut short, &packets[0]).is_err());
+    decryptor
+        .decrypt_packet(&mut duplicate, &packets[0])
+        .unwrap();
+
+    assert_eq!(message, decryption);
+
+    // Bytes, through the io adapters and through the grouped bit decryption.
+    encryptor.set_nonce(nonce.wrapping_add(2));
+    decryptor.set_nonce(nonce.wrapping_add(2));
+    let bytes = message
+        .chunks(8)
+        .map(|bits| bits.iter().fold(0_u8, |byte, &b| (byte << 1) | b as u8))
+        .collect::<Vec<_>>();
+
+    let mut writer = KeystreamWriter::new(encryptor, Vec::new());
+    writer.write_all(&bytes).unwrap();
+    let (mut encryptor, encrypted_bytes) = writer.into_inner();
+
+    let mut reader = KeystreamReader::new(decryptor, encrypted_bytes.as_slice());
+    let mut decrypted_bytes = Vec::new();
+    reader.read_to_end(&mut decrypted_bytes).unwrap();
+    let (mut decryptor, _) = reader.into_inner();
+    assert_eq!(bytes, decrypted_bytes);
+
+    encryptor.set_nonce(nonce.wrapping_add(3));
+    decryptor.set_nonce(nonce.wrapping_add(3));
+    let encrypted_bytes = encryptor.encrypt_bytes(&bytes);
+    let decrypted_bits = decryptor.decrypt_bytes(&encrypted_bytes);
+    assert_eq!(
+        bytes,
+        decrypted_bits
+            .iter()
+            .map(|bits| bits.iter().fold(0_u8, |byte, &b| (byte << 1) | b as u8))
+            .collect::<Vec<_>>()
+    );
+}
+
+// Client and server built independently from the same clear key.
+fn clear_key(parameters: &SystemParameters, n_iter: usize) {
+    let mut generator = RandomGenerator::new(None);
+    let nonce = generator.random_uniform();
+    let key_size = parameters.parameters().key_size();
+    let key = generator
+        .random_uniform_binary_tensor::<Torus>(key_size)
+        .as_slice()
+        .iter()
+        .map(|i| *i == 1)
+        .collect::<Vec<_>>();
+
+    let mut encryptor = Encrypter::from_clear_key(key.clone(), parameters, nonce).unwrap();
+    let mut decryptor = Encrypter::from_clear_key(key, parameters, nonce).unwrap();
+
+    let message = generator
+        .random_uniform_binary_tensor::<Torus>(n_iter)
+        .as_slice()
+        .iter()
+        .map(|i| *i == 1)
+        .collect::<Vec<_>>();
+    let mut ciphertext = vec![Default::default(); n_iter];
+    let mut decryption = vec![Default::default(); n_iter];
+
+    encryptor.encrypt(&mut ciphertext, &message);
+    decryptor.decrypt(&mut decryption, &ciphertext);
+
+    assert_eq!(message, decryption);
 }
@@ -12,36 +12,40 @@ use std::{
     io::{stdout, Write},
     time::Instant,
 };
-use FiLIP::{EncryptedBit, EncryptedKeyBit, Encrypter, SystemParameters, Torus};
+use FiLIP::{EncryptedBit, EncryptedKeyBit, Encrypter, NoiseMeasurement, SystemParameters, Torus};
 
 fn main() {
     let args: Vec<String> = env::args().collect();
     let n_iter = args[1].parse().unwrap();
+    let stages = args[2..].contains(&"--stages".to_string());
     let mut ran_test = false;
     if args[2..].contains(&"FiLIP_1280".to_string()) {
         println!("FiLIP 1280:");
-        homomorphic(&SystemParameters::n1280, n_iter);
+        homomorphic(&SystemParameters::n1280, n_iter, stages);
         println!("");
         ran_test = true;
     }
     if args[2..].contains(&"FiLIP_1216".to_string()) {
         println!("FiLIP 1216:");
-        homomorphic(&SystemParameters::n1216, n_iter);
+        homomorphic(&SystemParameters::n1216, n_iter, stages);
         println!("");
         ran_test = true;
     }
     if args[2..].contains(&"FiLIP_144".to_string()) {
         println!("FiLIP 144:");
-        homomorphic(&SystemParameters::n144, n_iter);
+        homomorphic(&SystemParameters::n144, n_iter, stages);
         println!("");
         ran_test = true;
     }
     if !ran_test {
-        panic!("Specify one or more version: FiLIP_144, FiLIP_1216 and/or FiLIP_1280.");
+        panic!(
+            "Specify one or more version: FiLIP_144, FiLIP_1216 and/or FiLIP_1280, \
+             and --stages to measure the noise of each part of the filter."
+        );
     }
 }
 
-fn homomorphic(parameters: &SystemParameters, n_iter: usize) {
+fn homomorphic(parameters: &SystemParameters, n_iter: usize, stages: bool) {
     let (glwe_dimension, poly_size, base_log, level, std_dev) = parameters.fhe_parameters();
 
     let sk = parameters.generate_fhe_key();
@@ -53,17 +57,21 @@ fn homomorphic(parameters: &SystemParameters, n_iter: usize) {
         .unwrap();
     stdout.flush().unwrap();
     stdout.queue(cursor::RestorePosition).unwrap();
+    let mut generator = RandomGenerator::new(None);
+    let nonce = generator.random_uniform();
+
     let now = Instant::now();
     let (mut encryptor, mut decryptor) = Encrypter::<bool>::new::<EncryptedKeyBit>(
         parameters,
+        nonce,
         Some(&sk),
         Some(level),
         Some(base_log),
         Some(std_dev),
-    );
+    )
+    .unwrap();
     println!("Trancrypter built in {} s.", now.elapsed().as_secs());
 
-    let mut generator = RandomGenerator::new(None);
     let message = generator
         .random_uniform_binary_tensor::<Torus>(n_iter)
         .as_slice()
@@ -113,6 +121,21 @@ fn homomorphic(parameters: &SystemParameters, n_iter: usize) {
         })
         .sum();
 
+    println!(
+        "Measured noise: {}.",
+        NoiseMeasurement::from_bits(&sk, &transciphered, &message)
+    );
+    println!("Predicted noise: {}.", parameters.noise_estimate());
+    if stages {
+        encryptor.seek(0);
+        decryptor.seek(0);
+        for (term, measurement) in
+            NoiseMeasurement::stages(&sk, &mut encryptor, &mut decryptor, n_iter)
+        {
+            println!("  {}: {}.", term, measurement);
+        }
+    }
+
     if errors > 0 {
         panic!(
             "{} error{} over {} bits.",
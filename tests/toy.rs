@@ -0,0 +1,218 @@
+//! End-to-end homomorphic checks on the insecure toy parameter sets, fast enough for CI.
+
+mod common;
+
+use concrete_commons::{key_kinds::BinaryKeyKind, parameters::PlaintextCount};
+use concrete_core::{
+    crypto::{encoding::PlaintextList, secret::GlweSecretKey},
+    math::{
+        random::RandomGenerator,
+        tensor::{AsRefSlice, AsRefTensor},
+    },
+};
+use FiLIP::{
+    Bit, Ciphertext, EncryptedBit, EncryptedKeyBit, Encrypter, FilterExpression, KeyBundle,
+    NoiseMeasurement, ParameterError, ParameterSet, ShadowBit, ShadowKeyBit, SystemParameters,
+    Torus, ToyParameters,
+};
+
+/// Client and server encrypters of a toy set, the server one built from a serialized bundle.
+fn encrypters(
+    set: &ParameterSet,
+    sk: &GlweSecretKey<BinaryKeyKind, Vec<Torus>>,
+    nonce: u128,
+) -> (Encrypter<bool>, Encrypter<EncryptedKeyBit>) {
+    let mut generator = RandomGenerator::new(None);
+    let key = (0..set.key_size)
+        .map(|_| generator.random_uniform_binary::<u8>() == 1)
+        .collect::<Vec<_>>();
+    let bundle = KeyBundle::from_parameter_set(set, &key, sk).unwrap();
+    let bundle = KeyBundle::deserialize(&bundle.serialize()).unwrap();
+    assert_eq!(bundle.parameters_name(), set.name);
+    (
+        Encrypter::from_parameter_set(key, set, nonce, true).unwrap(),
+        Encrypter::from_parameter_set(bundle.into_key(), set, nonce, true).unwrap(),
+    )
+}
+
+fn decrypt_bit(sk: &GlweSecretKey<BinaryKeyKind, Vec<Torus>>, bit: &EncryptedBit) -> bool {
+    let mut decrypted = PlaintextList::allocate(0, PlaintextCount(sk.polynomial_size().0));
+    sk.decrypt_glwe(&mut decrypted, bit.as_glwe());
+    let mut decoded = decrypted.as_tensor().as_slice()[0] >> (Torus::BITS as usize - 2);
+    if decoded % 2 == 1 {
+        decoded += 2;
+    }
+    (decoded >> 1) % 2 == 1
+}
+
+fn homomorphic(toy: ToyParameters) {
+    let set = toy.parameter_set(true).unwrap();
+    let (glwe_dimension, poly_size, _, _, _) = set.fhe_parameters();
+    let sk = set.generate_fhe_key();
+    let mut generator = RandomGenerator::new(None);
+    let (mut client, mut server) = encrypters(&set, &sk, generator.random_uniform());
+
+    let message = (0..64)
+        .map(|_| generator.random_uniform_binary::<u8>() == 1)
+        .collect::<Vec<_>>();
+    let packet = client.encrypt_packet(&message).unwrap();
+    let packet: Ciphertext = bincode::deserialize(&bincode::serialize(&packet).unwrap()).unwrap();
+
+    let mut transciphered =
+        vec![EncryptedBit::allocate(poly_size, glwe_dimension.to_glwe_size()); message.len()];
+    server.decrypt_packet(&mut transciphered, &packet).unwrap();
+    let decrypted = transciphered
+        .iter()
+        .map(|bit| decrypt_bit(&sk, bit))
+        .collect::<Vec<_>>();
+    assert_eq!(decrypted, message, "{}", set.name);
+}
+
+#[test]
+fn homomorphic_dsm() {
+    homomorphic(ToyParameters::dsm);
+}
+
+#[test]
+fn homomorphic_xor_thr() {
+    homomorphic(ToyParameters::xor_thr);
+}
+
+#[test]
+fn bundle_mismatch() {
+    // A bundle announcing a preset but made for a toy set is rejected, instead of panicking the
+    // server.
+    let mut set = ToyParameters::dsm.parameter_set(true).unwrap();
+    let sk = set.generate_fhe_key();
+
+    // Keys and secret keys that do not match the set are errors as well.
+    assert!(matches!(
+        KeyBundle::from_parameter_set(&set, &[false; 3], &sk),
+        Err(ParameterError::KeyLength { .. })
+    ));
+    let mut other = set.clone();
+    other.glwe_dimension += 1;
+    assert!(matches!(
+        KeyBundle::from_parameter_set(&other, &vec![false; set.key_size], &sk),
+        Err(ParameterError::Fhe(_))
+    ));
+
+    set.name = SystemParameters::n144.name();
+    let bundle = KeyBundle::from_parameter_set(&set, &vec![false; set.key_size], &sk).unwrap();
+    assert!(matches!(
+        Encrypter::from_bundle(bundle, 0),
+        Err(ParameterError::KeyLength { .. })
+    ));
+}
+
+#[test]
+fn short_key() {
+    // A key shorter than the filter input is rejected up front, instead of panicking on the
+    // first keystream bit.
+    let set = ToyParameters::dsm.parameter_set(true).unwrap();
+    assert_eq!(
+        Encrypter::with_filter(vec![false; 4], set.n, set.filter, set.prng, 0).err(),
+        Some(ParameterError::KeySize { n: 8, key_size: 4 })
+    );
+}
+
+#[test]
+fn multibit_dsm() {
+    let set = ToyParameters::dsm.parameter_set(true).unwrap();
+    common::multibit(&set, true, 8, 2);
+}
+
+#[test]
+fn multibit_xor_thr() {
+    let set = ToyParameters::xor_thr.parameter_set(true).unwrap();
+    common::multibit(&set, true, 8, 2);
+}
+
+#[test]
+fn noise() {
+    let set = ToyParameters::dsm.parameter_set(true).unwrap();
+    let sk = set.generate_fhe_key();
+    let mut generator = RandomGenerator::new(None);
+    let (mut client, mut server) = encrypters(&set, &sk, generator.random_uniform());
+
+    let message = (0..32)
+        .map(|_| generator.random_uniform_binary::<u8>() == 1)
+        .collect::<Vec<_>>();
+    let mut ciphertext = vec![false; message.len()];
+    let mut transciphered = vec![
+        EncryptedBit::allocate(
+            set.fhe_parameters().1,
+            set.fhe_parameters().0.to_glwe_size()
+        );
+        message.len()
+    ];
+    client.encrypt(&mut ciphertext, &message);
+    server.decrypt(&mut transciphered, &ciphertext);
+    let measurement = NoiseMeasurement::from_bits(&sk, &transciphered, &message);
+    let predicted = set.noise_estimate();
+    assert_eq!(measurement.count(), message.len() * set.polynomial_size);
+    assert!(measurement.max() < 0.25);
+    assert!(
+        measurement.estimate().variance() < 2. * predicted.variance(),
+        "measured {}, predicted {}",
+        measurement,
+        predicted
+    );
+
+    // The toy DSM filter is the xor of two inputs and of two monomials of degree three, whose
+    // noises add up to the noise of the keystream bits.
+    client.seek(0);
+    server.seek(0);
+    let stages = NoiseMeasurement::stages(&sk, &mut client, &mut server, message.len());
+    assert_eq!(
+        stages.iter().map(|(term, _)| term).collect::<Vec<_>>(),
+        vec![&FilterExpression::Linear(2), &FilterExpression::Monomial(3)]
+    );
+    assert_eq!(stages[1].1.count(), 2 * measurement.count());
+    let total = stages
+        .iter()
+        .map(|(_, stage)| stage.estimate().variance() * stage.count() as f64)
+        .sum::<f64>()
+        / measurement.count() as f64;
+    assert!((total / measurement.estimate().variance() - 1.).abs() < 0.2);
+}
+
+#[test]
+fn shadow() {
+    let set = ToyParameters::dsm.parameter_set(true).unwrap();
+    let (glwe_dimension, poly_size, base_log, level, std_dev) = set.fhe_parameters();
+    let sk = set.generate_fhe_key();
+    let mut generator = RandomGenerator::new(None);
+    let key = (0..set.key_size)
+        .map(|_| generator.random_uniform_binary::<u8>() == 1)
+        .collect::<Vec<_>>();
+    let nonce = generator.random_uniform();
+    let message = (0..16).map(|i| i % 3 == 0).collect::<Vec<_>>();
+    let mut ciphertext = vec![false; message.len()];
+    Encrypter::from_parameter_set(key.clone(), &set, nonce, true)
+        .unwrap()
+        .encrypt(&mut ciphertext, &message);
+
+    let shadowed = ShadowKeyBit::encrypt_key(&key, &sk, level, base_log, std_dev);
+    let mut server = Encrypter::from_parameter_set(shadowed, &set, nonce, true).unwrap();
+    let mut transciphered =
+        vec![ShadowBit::zero(Some(poly_size), Some(glwe_dimension.to_glwe_size())); message.len()];
+    server.decrypt(&mut transciphered, &ciphertext);
+    for (bit, &m) in transciphered.iter().zip(&message) {
+        assert_eq!(bit.value(), m);
+        assert_eq!(decrypt_bit(&sk, bit.bit()), m);
+    }
+    assert!(server.key()[0].operations() > 0);
+    assert_eq!(server.key()[0].divergence(), None);
+
+    // Paired with the negation of its clear key, the encrypted key goes wrong at the very first
+    // operation.
+    let encrypted = EncryptedKeyBit::encrypt_key(&key, &sk, level, base_log, std_dev);
+    let wrong = key.iter().map(|bit| !bit).collect::<Vec<_>>();
+    let shadowed = ShadowKeyBit::from_key(encrypted, &wrong, &sk);
+    let mut server = Encrypter::from_parameter_set(shadowed, &set, nonce, true).unwrap();
+    server.decrypt(&mut transciphered[..1], &ciphertext[..1]);
+    let divergence = transciphered[0].divergence().unwrap();
+    assert_eq!(divergence.index, 0);
+    assert!(divergence.noise.max() >= 0.25, "{}", divergence);
+}